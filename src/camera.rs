@@ -1,4 +1,108 @@
-use na::{Matrix4, Perspective3, Point3, UnitQuaternion, Vector3};
+use na::{Matrix4, Orthographic3, Perspective3, Point2, Point3, UnitQuaternion, Vector3};
+use plane::aabb::*;
+use volume::frustum::*;
+use volume::ray::*;
+use volume::*;
+
+/// How a `Camera` maps its view space onto clip space.
+#[derive(Copy, Clone, Debug)]
+pub enum Projection {
+    Perspective(Perspective3<f32>),
+    Orthographic(Orthographic3<f32>),
+    /// Any other matrix, which `resize` leaves alone.
+    Custom(Matrix4<f32>),
+}
+
+impl Projection {
+    pub fn matrix(&self) -> Matrix4<f32> {
+        match self {
+            Projection::Perspective(p) => *p.as_matrix(),
+            Projection::Orthographic(o) => *o.as_matrix(),
+            Projection::Custom(m) => *m,
+        }
+    }
+
+    /// Makes the width over the height `aspect`, keeping the height. Orthographic views stay
+    /// centered where they were.
+    pub fn set_aspect(&mut self, aspect: f32) {
+        match self {
+            Projection::Perspective(p) => p.set_aspect(aspect),
+            Projection::Orthographic(o) => {
+                let center = (o.left() + o.right()) / 2.0;
+                let half_width = (o.top() - o.bottom()) * aspect / 2.0;
+                o.set_left_and_right(center - half_width, center + half_width);
+            }
+            Projection::Custom(_) => {}
+        }
+    }
+
+    /// An orthographic projection that frames things `distance` ahead as this one does, with the
+    /// same aspect ratio and clipping planes. Returns `None` for custom projections.
+    pub fn to_orthographic(&self, distance: f32) -> Option<Orthographic3<f32>> {
+        match self {
+            Projection::Perspective(p) => {
+                let half_height = distance * (p.fovy() / 2.0).tan();
+                let half_width = half_height * p.aspect();
+                Some(Orthographic3::new(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    p.znear(),
+                    p.zfar(),
+                ))
+            }
+            Projection::Orthographic(o) => Some(*o),
+            Projection::Custom(_) => None,
+        }
+    }
+
+    /// A perspective projection that frames things `distance` ahead as this one does, with the
+    /// same aspect ratio and clipping planes. Off-center orthographic views end up centered.
+    /// Returns `None` for custom projections.
+    pub fn to_perspective(&self, distance: f32) -> Option<Perspective3<f32>> {
+        match self {
+            Projection::Perspective(p) => Some(*p),
+            Projection::Orthographic(o) => {
+                let height = o.top() - o.bottom();
+                let fovy = 2.0 * (height / 2.0 / distance).atan();
+                Some(Perspective3::new(
+                    (o.right() - o.left()) / height,
+                    fovy,
+                    o.znear(),
+                    o.zfar(),
+                ))
+            }
+            Projection::Custom(_) => None,
+        }
+    }
+}
+
+/// The projections a `Camera` can switch between while keeping its framing; see
+/// `Camera::switch_projection`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProjectionKind {
+    Perspective,
+    Orthographic,
+}
+
+impl From<Perspective3<f32>> for Projection {
+    fn from(p: Perspective3<f32>) -> Self {
+        Projection::Perspective(p)
+    }
+}
+
+impl From<Orthographic3<f32>> for Projection {
+    fn from(o: Orthographic3<f32>) -> Self {
+        Projection::Orthographic(o)
+    }
+}
+
+impl From<Matrix4<f32>> for Projection {
+    fn from(m: Matrix4<f32>) -> Self {
+        Projection::Custom(m)
+    }
+}
 
 #[derive(Debug)]
 pub struct Camera {
@@ -7,39 +111,103 @@ pub struct Camera {
     pub pos: Point3<f32>,
     pub forward: Vector3<f32>,
     pub up: Vector3<f32>,
-    pub persp: Perspective3<f32>,
+    pub projection: Projection,
 }
 
 impl Camera {
-    pub fn new(pos: Point3<f32>, persp: Perspective3<f32>) -> Camera {
+    pub fn new<P: Into<Projection>>(pos: Point3<f32>, projection: P) -> Camera {
         Camera {
             stale: true,
             cache: Matrix4::identity(),
             pos,
             forward: Vector3::z(),
             up: Vector3::y(),
-            persp,
+            projection: projection.into(),
         }
     }
 
     pub fn fresh_mat(&mut self) -> &Matrix4<f32> {
         if self.stale {
-            self.cache = self.persp.as_matrix()
-                * Matrix4::look_at_rh(&self.pos, &(self.pos + self.forward), &self.up);
+            self.cache = self.view_projection();
             self.stale = false;
         }
         &self.cache
     }
 
+    /// The planes bounding what the camera sees, in world space, from the cached matrix.
+    pub fn frustum(&mut self) -> Frustum {
+        Frustum::from_matrix(&na::convert(*self.fresh_mat()))
+    }
+
+    /// The matrix `fresh_mat` caches, without touching the cache.
+    fn view_projection(&self) -> Matrix4<f32> {
+        if !self.stale {
+            return self.cache;
+        }
+        self.projection.matrix()
+            * Matrix4::look_at_rh(&self.pos, &(self.pos + self.forward), &self.up)
+    }
+
+    /// The point in the world that appears at `pixel` with the given normalized depth, from -1 on
+    /// the near plane to 1 on the far one.
+    ///
+    /// Pixels are measured as `ndc_to_screen` produces them, over `viewport`. Returns `None` if the
+    /// camera can't see anything, as when `forward` and `up` are parallel.
+    pub fn unproject(
+        &self,
+        pixel: &Point2<f64>,
+        depth: f64,
+        viewport: &Aabb2<f64>,
+    ) -> Option<Point3<f64>> {
+        let inverse = na::convert::<_, Matrix4<f64>>(self.view_projection()).try_inverse()?;
+        let ndc = screen_to_ndc(pixel, &Point2::from(viewport.size()), &viewport.min.coords);
+        Point3::from_homogeneous(inverse * Point3::new(ndc.x, ndc.y, depth).to_homogeneous())
+    }
+
+    /// The ray through everything in the world that appears at `pixel`, starting on the near
+    /// plane; see `unproject`.
+    pub fn screen_ray(&self, pixel: &Point2<f64>, viewport: &Aabb2<f64>) -> Option<Ray3> {
+        let near = self.unproject(pixel, -1.0, viewport)?;
+        let far = self.unproject(pixel, 1.0, viewport)?;
+        Some(Ray3::towards(near, &far))
+    }
+
     pub fn right(&self) -> Vector3<f32> {
         self.up.cross(&self.forward) // (1, 0, 0) if up is (0, 1, 0) and forward is (0, 0, 1)
     }
 
     pub fn resize(&mut self, width: f32, height: f32) {
-        self.persp.set_aspect(width / height);
+        self.projection.set_aspect(width / height);
         self.stale = true;
     }
 
+    pub fn set_projection<P: Into<Projection>>(&mut self, projection: P) {
+        self.projection = projection.into();
+        self.stale = true;
+    }
+
+    /// Switches to a projection of the given kind that frames things `focus_distance` ahead as
+    /// the current one does. Returns `false`, leaving the projection alone, if it is custom.
+    pub fn switch_projection(&mut self, kind: ProjectionKind, focus_distance: f32) -> bool {
+        let projection = match kind {
+            ProjectionKind::Perspective => self
+                .projection
+                .to_perspective(focus_distance)
+                .map(Projection::from),
+            ProjectionKind::Orthographic => self
+                .projection
+                .to_orthographic(focus_distance)
+                .map(Projection::from),
+        };
+        match projection {
+            Some(p) => {
+                self.set_projection(p);
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn mov(&mut self, x: f32, y: f32, z: f32) {
         self.pos += self.forward * z;
         self.pos += self.up * y;
@@ -62,3 +230,153 @@ impl Camera {
         self.stale = true;
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use volume::polyhedron::*;
+
+    fn camera() -> Camera {
+        let mut cam = Camera::new(
+            Point3::new(1.0, 0.0, -5.0),
+            Perspective3::new(2.0, 1.0, 0.1, 100.0),
+        );
+        cam.look_at(&Point3::origin());
+        cam
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut cam = camera();
+        let viewport = Aabb2::new(&Point2::new(10.0, 20.0), &Point2::new(810.0, 420.0));
+        let p = Point3::new(0.25, -0.5, 0.5);
+        let clip: Matrix4<f64> = na::convert(*cam.fresh_mat());
+        let clip = clip * p.to_homogeneous();
+        let ndc = Point3::from_homogeneous(clip).unwrap();
+        let pixel = ndc_to_screen(
+            &Point3::new(ndc.x, ndc.y, 1.0),
+            &Point2::from(viewport.size()),
+            &viewport.min.coords,
+        );
+        let back = cam.unproject(&pixel, ndc.z, &viewport).unwrap();
+        assert_relative_eq!(back, p, epsilon = 1.0e-4);
+        let ray = cam.screen_ray(&pixel, &viewport).unwrap();
+        let along = p - ray.origin;
+        assert_relative_eq!(along.normalize(), ray.dir.into_inner(), epsilon = 1.0e-4);
+    }
+
+    #[test]
+    fn pick() {
+        let cam = camera();
+        let viewport = Aabb2::new(&Point2::new(0.0, 0.0), &Point2::new(800.0, 400.0));
+        let ray = cam.screen_ray(&viewport.center(), &viewport).unwrap();
+        assert_relative_eq!(
+            ray.dir.into_inner(),
+            -Vector3::new(1.0, 0.0, -5.0).normalize(),
+            epsilon = 1.0e-5
+        );
+        let mesh = Polyhedron::cube();
+        let hit = mesh.raycast(&ray, true).unwrap();
+        // the ray enters the cube through its -z face
+        assert_relative_eq!(hit.normal.into_inner(), -Vector3::z(), epsilon = 1.0e-9);
+        assert!(mesh
+            .raycast(
+                &cam.screen_ray(&Point2::new(0.0, 0.0), &viewport).unwrap(),
+                true
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn culling() {
+        let mut cam = camera();
+        let frustum = cam.frustum();
+        assert_eq!(
+            frustum.test_polyhedron(&Polyhedron::cube()),
+            Visibility::Inside
+        );
+        let behind = Polyhedron::cube().map(|p| [p[0] + 2.0, p[1], p[2] - 10.0]);
+        assert_eq!(frustum.test_polyhedron(&behind), Visibility::Outside);
+        assert!(!frustum.contains_point(&Point3::new(1.0, 0.0, -5.05)));
+        assert!(frustum.contains_point(&Point3::new(1.0, 0.0, -4.85)));
+    }
+
+    /// Where a world point lands in normalized device coordinates.
+    fn ndc(cam: &mut Camera, p: &Point3<f32>) -> Point3<f32> {
+        Point3::from_homogeneous(cam.fresh_mat() * p.to_homogeneous()).unwrap()
+    }
+
+    #[test]
+    fn orthographic() {
+        let mut cam = Camera::new(
+            Point3::new(0.0, 0.0, -5.0),
+            Orthographic3::new(-2.0, 2.0, -1.0, 1.0, 0.1, 100.0),
+        );
+        let viewport = Aabb2::new(&Point2::new(0.0, 0.0), &Point2::new(400.0, 200.0));
+        let a = cam.screen_ray(&Point2::new(0.0, 0.0), &viewport).unwrap();
+        let b = cam
+            .screen_ray(&Point2::new(300.0, 150.0), &viewport)
+            .unwrap();
+        // every ray runs straight ahead, from wherever its pixel is
+        assert_relative_eq!(a.dir.into_inner(), Vector3::z(), epsilon = 1.0e-6);
+        assert_relative_eq!(b.dir.into_inner(), Vector3::z(), epsilon = 1.0e-6);
+        assert_relative_eq!(a.origin.y, -1.0, epsilon = 1.0e-6);
+        assert_relative_eq!(b.origin.y, 0.5, epsilon = 1.0e-6);
+        cam.resize(600.0, 200.0);
+        match cam.projection {
+            Projection::Orthographic(o) => {
+                assert_relative_eq!(o.left(), -3.0);
+                assert_relative_eq!(o.right(), 3.0);
+                assert_relative_eq!(o.top(), 1.0);
+            }
+            p => panic!("Expected an orthographic projection, got {:?}", p),
+        }
+        let mut custom = Camera::new(Point3::new(0.0, 0.0, -5.0), cam.projection.matrix());
+        custom.resize(100.0, 100.0);
+        assert_relative_eq!(*custom.fresh_mat(), *cam.fresh_mat());
+    }
+
+    #[test]
+    fn switching() {
+        let mut cam = Camera::new(
+            Point3::new(0.0, 0.0, -5.0),
+            Perspective3::new(1.5, 1.0, 0.1, 100.0),
+        );
+        // a point 5 ahead, at the focus, stays put when switching either way
+        let p = Point3::new(0.75, -0.5, 0.0);
+        let before = ndc(&mut cam, &p);
+        let ortho = cam.projection.to_orthographic(5.0).unwrap();
+        cam.set_projection(ortho);
+        let after = ndc(&mut cam, &p);
+        assert_relative_eq!(before.x, after.x, epsilon = 1.0e-5);
+        assert_relative_eq!(before.y, after.y, epsilon = 1.0e-5);
+        let persp = cam.projection.to_perspective(5.0).unwrap();
+        assert_relative_eq!(persp.fovy(), 1.0, epsilon = 1.0e-6);
+        assert_relative_eq!(persp.aspect(), 1.5, epsilon = 1.0e-6);
+        cam.set_projection(persp);
+        assert_relative_eq!(ndc(&mut cam, &p), before, epsilon = 1.0e-5);
+        assert!(Projection::Custom(Matrix4::identity())
+            .to_orthographic(5.0)
+            .is_none());
+    }
+
+    #[test]
+    fn switch_projection() {
+        let mut cam = camera();
+        let p = Point3::new(0.25, -0.5, 0.0);
+        let focus = (p - cam.pos).dot(&cam.forward);
+        let before = ndc(&mut cam, &p);
+        assert!(cam.switch_projection(ProjectionKind::Orthographic, focus));
+        match cam.projection {
+            Projection::Orthographic(_) => {}
+            _ => panic!("expected an orthographic projection"),
+        }
+        let after = ndc(&mut cam, &p);
+        assert_relative_eq!(before.x, after.x, epsilon = 1.0e-5);
+        assert_relative_eq!(before.y, after.y, epsilon = 1.0e-5);
+        assert!(cam.switch_projection(ProjectionKind::Perspective, focus));
+        assert_relative_eq!(ndc(&mut cam, &p), before, epsilon = 1.0e-5);
+        cam.set_projection(Matrix4::identity());
+        assert!(!cam.switch_projection(ProjectionKind::Orthographic, focus));
+    }
+}
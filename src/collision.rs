@@ -0,0 +1,268 @@
+//! The parts of the collision queries in `plane` and `volume` that work the same in any dimension:
+//! the result types, the separating axis test over a given set of axes, and the GJK search up to
+//! the point where it needs to know what a full simplex looks like.
+
+use na::{Point2, Point3, Unit, Vector2, Vector3};
+use std::fmt::Debug;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// Iterations either GJK or EPA may take before settling for its best answer so far.
+pub(crate) const MAX_ITERATIONS: usize = 64;
+/// How much closer, relative to the current distance, a step must get for a search to go on.
+pub(crate) const TOLERANCE: f64 = 1.0e-10;
+
+/// A vector type the collision queries can work in, along with its points.
+pub trait Space:
+    Copy
+    + Debug
+    + PartialEq
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Neg<Output = Self>
+    + Mul<f64, Output = Self>
+{
+    type Point: Copy + Debug;
+
+    fn zeros() -> Self;
+    /// Any fixed direction, for searches that need somewhere to start.
+    fn x() -> Self;
+    fn dot(&self, other: &Self) -> f64;
+    fn from_point(p: &Self::Point) -> Self;
+    fn to_point(self) -> Self::Point;
+
+    fn norm_squared(&self) -> f64 {
+        self.dot(self)
+    }
+}
+
+impl Space for Vector2<f64> {
+    type Point = Point2<f64>;
+
+    fn zeros() -> Self {
+        Vector2::zeros()
+    }
+
+    fn x() -> Self {
+        Vector2::x()
+    }
+
+    fn dot(&self, other: &Self) -> f64 {
+        Vector2::dot(self, other)
+    }
+
+    fn from_point(p: &Point2<f64>) -> Self {
+        p.coords
+    }
+
+    fn to_point(self) -> Point2<f64> {
+        Point2::from(self)
+    }
+}
+
+impl Space for Vector3<f64> {
+    type Point = Point3<f64>;
+
+    fn zeros() -> Self {
+        Vector3::zeros()
+    }
+
+    fn x() -> Self {
+        Vector3::x()
+    }
+
+    fn dot(&self, other: &Self) -> f64 {
+        Vector3::dot(self, other)
+    }
+
+    fn from_point(p: &Point3<f64>) -> Self {
+        p.coords
+    }
+
+    fn to_point(self) -> Point3<f64> {
+        Point3::from(self)
+    }
+}
+
+/// How two shapes overlap: moving the second by `axis * depth` (or the first by the opposite)
+/// separates them by the shortest distance possible.
+#[derive(Copy, Clone, Debug)]
+pub struct Penetration<V> {
+    /// Points from the first shape towards the second.
+    pub axis: Unit<V>,
+    pub depth: f64,
+}
+
+/// The lowest and highest projections of some points onto an axis.
+pub(crate) fn project<'a, V, I>(points: I, axis: &V) -> [f64; 2]
+where
+    V: Space + 'a,
+    I: IntoIterator<Item = &'a V::Point>,
+{
+    points
+        .into_iter()
+        .fold([f64::INFINITY, f64::NEG_INFINITY], |[lo, hi], p| {
+            let d = V::from_point(p).dot(axis);
+            [lo.min(d), hi.max(d)]
+        })
+}
+
+/// Projects two point sets onto each axis in turn, returning `None` as soon as one separates them
+/// and otherwise the smallest push along any of them that would.
+pub(crate) fn separating_axes<V, I>(
+    axes: I,
+    a: &[V::Point],
+    b: &[V::Point],
+) -> Option<Penetration<V>>
+where
+    V: Space,
+    I: IntoIterator<Item = Unit<V>>,
+{
+    let mut res: Option<Penetration<V>> = None;
+    for axis in axes {
+        let (pa, pb) = (project(a, axis.as_ref()), project(b, axis.as_ref()));
+        let (forward, back) = (pa[1] - pb[0], pb[1] - pa[0]);
+        if forward < 0.0 || back < 0.0 {
+            return None;
+        }
+        let pen = if forward <= back {
+            Penetration {
+                axis,
+                depth: forward,
+            }
+        } else {
+            Penetration {
+                axis: Unit::new_unchecked(-axis.into_inner()),
+                depth: back,
+            }
+        };
+        if res.map_or(true, |r| pen.depth < r.depth) {
+            res = Some(pen);
+        }
+    }
+    res
+}
+
+/// The closest points of two shapes that don't overlap.
+#[derive(Copy, Clone, Debug)]
+pub struct Separation<V: Space> {
+    /// The closest point on each shape.
+    pub points: [V::Point; 2],
+    pub distance: f64,
+}
+
+/// How two shapes overlap: moving the second by `normal * depth` (or the first by the opposite)
+/// separates them by the shortest distance possible.
+#[derive(Copy, Clone, Debug)]
+pub struct Contact<V: Space> {
+    /// Points from the first shape towards the second.
+    pub normal: Unit<V>,
+    pub depth: f64,
+    /// The point of each shape deepest inside the other.
+    pub points: [V::Point; 2],
+}
+
+/// A point of the Minkowski difference, along with the points of each shape it came from.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Vert<V: Space> {
+    pub(crate) p: V,
+    pub(crate) a: V::Point,
+    pub(crate) b: V::Point,
+}
+
+impl<V: Space> Vert<V> {
+    /// The vertex for the points `a` and `b` of the two shapes.
+    pub(crate) fn new(a: V::Point, b: V::Point) -> Self {
+        Vert {
+            p: V::from_point(&a) - V::from_point(&b),
+            a,
+            b,
+        }
+    }
+}
+
+/// Combines the shapes' points behind some vertices by barycentric weights.
+pub(crate) fn combine<V: Space>(verts: &[Vert<V>], weights: &[f64]) -> [V::Point; 2] {
+    let (mut a, mut b) = (V::zeros(), V::zeros());
+    for (v, w) in verts.iter().zip(weights) {
+        a = a + V::from_point(&v.a) * *w;
+        b = b + V::from_point(&v.b) * *w;
+    }
+    [a.to_point(), b.to_point()]
+}
+
+/// How far along the segment `pq` its point closest to the origin is.
+pub(crate) fn segment_weight<V: Space>(p: &V, q: &V) -> f64 {
+    let d = *q - *p;
+    if d.norm_squared() > 0.0 {
+        (-p.dot(&d) / d.norm_squared()).max(0.0).min(1.0)
+    } else {
+        0.0
+    }
+}
+
+pub(crate) enum Gjk<V: Space> {
+    Separated(Separation<V>),
+    /// The final simplex, which contains the origin or passes within rounding of it.
+    Overlapping(Vec<Vert<V>>),
+}
+
+/// Searches the Minkowski difference of two shapes, seen through `support`, for its point closest
+/// to the origin.
+///
+/// Points and segments are handled here; `closest` only sees simplices of three or more vertices,
+/// and gives the barycentric weights of their point closest to the origin, or `None` if the
+/// simplex is as large as the space allows and contains it.
+pub(crate) fn gjk<V, S, C>(support: S, closest: C) -> Gjk<V>
+where
+    V: Space,
+    S: Fn(&V) -> Vert<V>,
+    C: Fn(&[Vert<V>]) -> Option<Vec<f64>>,
+{
+    let mut simplex = vec![support(&V::x())];
+    let mut best = None;
+    for _ in 0..MAX_ITERATIONS {
+        let weights = match simplex.len() {
+            1 => vec![1.0],
+            2 => {
+                let t = segment_weight(&simplex[0].p, &simplex[1].p);
+                vec![1.0 - t, t]
+            }
+            _ => match closest(&simplex) {
+                Some(w) => w,
+                None => return Gjk::Overlapping(simplex),
+            },
+        };
+        // only the vertices the closest point depends on are needed from here
+        let (verts, weights): (Vec<Vert<V>>, Vec<f64>) = simplex
+            .iter()
+            .zip(weights)
+            .filter(|(_, w)| *w > 0.0)
+            .map(|(v, w)| (*v, w))
+            .unzip();
+        simplex = verts;
+        let v = simplex
+            .iter()
+            .zip(&weights)
+            .fold(V::zeros(), |acc, (s, w)| acc + s.p * *w);
+        let scale = simplex
+            .iter()
+            .map(|s| s.p.norm_squared())
+            .fold(0.0, f64::max);
+        if v.norm_squared() <= f64::EPSILON * scale {
+            return Gjk::Overlapping(simplex);
+        }
+        best = Some(Separation {
+            points: combine(&simplex, &weights),
+            distance: v.norm_squared().sqrt(),
+        });
+        let next = support(&-v);
+        // stop once the support point gets no closer than where we already are
+        if v.norm_squared() - v.dot(&next.p) <= TOLERANCE * v.norm_squared()
+            || simplex.iter().any(|s| s.p == next.p)
+        {
+            break;
+        }
+        simplex.push(next);
+    }
+    Gjk::Separated(best.unwrap())
+}
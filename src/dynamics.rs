@@ -0,0 +1,334 @@
+//! Rigid bodies moved by semi-implicit Euler integration over a fixed timestep.
+//!
+//! Everything here is plain sequential `f64` arithmetic with no dependence on wall-clock time, so
+//! the same bodies fed the same forces always end up in bit-for-bit the same state.
+
+use na::{Isometry3, Matrix3, Point3, Translation3, UnitQuaternion, Vector3};
+use volume::polyhedron::*;
+
+#[derive(Clone, Debug)]
+pub struct RigidBody {
+    /// The body's surface, in body space with the center of mass at the origin.
+    pub shape: Polyhedron<Point3<f64>>,
+    pub mass: f64,
+    /// The inertia tensor about the center of mass, in body space.
+    pub inertia: Matrix3<f64>,
+    inverse_inertia: Matrix3<f64>,
+    /// Where the center of mass is in the world.
+    pub position: Point3<f64>,
+    pub orientation: UnitQuaternion<f64>,
+    pub linear_velocity: Vector3<f64>,
+    /// Angular velocity in world space, as a scaled axis in radians per second.
+    pub angular_velocity: Vector3<f64>,
+    force: Vector3<f64>,
+    torque: Vector3<f64>,
+}
+
+impl RigidBody {
+    /// Creates a body at rest from a closed mesh of uniform density.
+    ///
+    /// The body is placed where the mesh was, with `position` at its center of mass and an
+    /// identity orientation.
+    ///
+    /// # Panics
+    /// If the mesh has no volume, or the density isn't finite and positive, since either would
+    /// leave the body without a finite, positive mass.
+    pub fn new<P: Vertex>(shape: &Polyhedron<P>, density: f64) -> Self {
+        let props = shape.mass_properties();
+        assert!(
+            props.volume > 0.0 && props.volume.is_finite(),
+            "Rigid bodies need a closed mesh with volume, got {}",
+            props.volume
+        );
+        assert!(
+            density > 0.0 && density.is_finite(),
+            "Rigid bodies need a finite, positive density, got {}",
+            density
+        );
+        let mass = props.volume * density;
+        assert!(mass.is_finite(), "Rigid body mass overflowed");
+        let inertia = props.inertia * density;
+        RigidBody {
+            shape: Polyhedron {
+                points: shape
+                    .points
+                    .iter()
+                    .map(|p| Point3::from(p.position() - props.center_of_mass))
+                    .collect(),
+                faces: shape.faces.clone(),
+            },
+            mass,
+            inertia,
+            inverse_inertia: inertia.try_inverse().unwrap_or_else(Matrix3::zeros),
+            position: props.center_of_mass,
+            orientation: UnitQuaternion::identity(),
+            linear_velocity: Vector3::zeros(),
+            angular_velocity: Vector3::zeros(),
+            force: Vector3::zeros(),
+            torque: Vector3::zeros(),
+        }
+    }
+
+    /// Moves points from body space into the world.
+    pub fn transform(&self) -> Isometry3<f64> {
+        Isometry3::from_parts(Translation3::from(self.position.coords), self.orientation)
+    }
+
+    /// The inertia tensor about the center of mass, in world space.
+    pub fn world_inertia(&self) -> Matrix3<f64> {
+        let r = self.orientation.to_rotation_matrix();
+        r.matrix() * self.inertia * r.matrix().transpose()
+    }
+
+    pub fn world_inverse_inertia(&self) -> Matrix3<f64> {
+        let r = self.orientation.to_rotation_matrix();
+        r.matrix() * self.inverse_inertia * r.matrix().transpose()
+    }
+
+    /// The velocity of the point of the body currently at `point` in the world.
+    pub fn velocity_at(&self, point: &Point3<f64>) -> Vector3<f64> {
+        self.linear_velocity + self.angular_velocity.cross(&(point - self.position))
+    }
+
+    /// Applies a force through the center of mass until the next step.
+    pub fn apply_force(&mut self, force: &Vector3<f64>) {
+        self.force += force;
+    }
+
+    /// Applies a force at a point in the world until the next step.
+    pub fn apply_force_at(&mut self, force: &Vector3<f64>, point: &Point3<f64>) {
+        self.force += force;
+        self.torque += (point - self.position).cross(force);
+    }
+
+    /// Applies a torque, in world space, until the next step.
+    pub fn apply_torque(&mut self, torque: &Vector3<f64>) {
+        self.torque += torque;
+    }
+
+    /// Changes the body's momentum immediately, as if `impulse` were applied at a point in the
+    /// world.
+    pub fn apply_impulse_at(&mut self, impulse: &Vector3<f64>, point: &Point3<f64>) {
+        self.linear_velocity += impulse / self.mass;
+        self.angular_velocity +=
+            self.world_inverse_inertia() * (point - self.position).cross(impulse);
+    }
+
+    pub fn kinetic_energy(&self) -> f64 {
+        (self.mass * self.linear_velocity.norm_squared()
+            + self
+                .angular_velocity
+                .dot(&(self.world_inertia() * self.angular_velocity)))
+            / 2.0
+    }
+
+    /// Advances the body by `dt` seconds under its accumulated forces plus `gravity`, then clears
+    /// the forces.
+    ///
+    /// Velocities are updated first and then used to move the body (semi-implicit Euler). The
+    /// gyroscopic term `ω × Iω` is included, so bodies with unequal principal moments tumble as
+    /// they should.
+    pub fn step(&mut self, dt: f64, gravity: &Vector3<f64>) {
+        self.linear_velocity += (self.force / self.mass + gravity) * dt;
+        let inertia = self.world_inertia();
+        let gyroscopic = self
+            .angular_velocity
+            .cross(&(inertia * self.angular_velocity));
+        self.angular_velocity += self.world_inverse_inertia() * (self.torque - gyroscopic) * dt;
+        self.position += self.linear_velocity * dt;
+        self.orientation =
+            UnitQuaternion::from_scaled_axis(self.angular_velocity * dt) * self.orientation;
+        // keep rounding from slowly denormalizing the rotation
+        self.orientation.renormalize();
+        self.force = Vector3::zeros();
+        self.torque = Vector3::zeros();
+    }
+}
+
+/// A set of bodies stepped together at a fixed rate.
+#[derive(Clone, Debug)]
+pub struct World {
+    pub bodies: Vec<RigidBody>,
+    pub gravity: Vector3<f64>,
+    /// Seconds per step.
+    pub timestep: f64,
+    /// Time passed to `advance` that hasn't made up a whole step yet.
+    leftover: f64,
+}
+
+impl World {
+    pub fn new(timestep: f64, gravity: Vector3<f64>) -> Self {
+        // `advance` would never catch up on a step that takes no time
+        assert!(
+            timestep > 0.0 && timestep.is_finite(),
+            "Worlds need a finite, positive timestep, got {}",
+            timestep
+        );
+        World {
+            bodies: Vec::new(),
+            gravity,
+            timestep,
+            leftover: 0.0,
+        }
+    }
+
+    /// Adds a body, returning its index in `bodies`.
+    pub fn add(&mut self, body: RigidBody) -> usize {
+        self.bodies.push(body);
+        self.bodies.len() - 1
+    }
+
+    /// Steps every body once, in order.
+    pub fn step(&mut self) {
+        for body in &mut self.bodies {
+            body.step(self.timestep, &self.gravity);
+        }
+    }
+
+    /// Runs as many whole steps as fit in `elapsed` plus what was left over last time, and returns
+    /// how many ran. The remainder carries over, so frame times never change the simulation itself.
+    pub fn advance(&mut self, elapsed: f64) -> usize {
+        self.leftover += elapsed;
+        let mut steps = 0;
+        while self.leftover >= self.timestep {
+            self.leftover -= self.timestep;
+            self.step();
+            steps += 1;
+        }
+        steps
+    }
+
+    /// How far the world is between its last step and the next, from 0 to 1, for interpolating
+    /// what's drawn.
+    pub fn alpha(&self) -> f64 {
+        self.leftover / self.timestep
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn free_fall() {
+        let mut world = World::new(0.01, Vector3::new(0.0, -10.0, 0.0));
+        let body = world.add(RigidBody::new(&Polyhedron::cube(), 2.0));
+        assert_relative_eq!(world.bodies[body].mass, 2.0);
+        for _ in 0..100 {
+            world.step();
+        }
+        let body = &world.bodies[body];
+        assert_relative_eq!(body.linear_velocity.y, -10.0, epsilon = 1.0e-9);
+        // semi-implicit Euler falls a little further than the exact 5
+        assert_relative_eq!(
+            body.position.y,
+            -10.0 * 0.01 * 0.01 * 5050.0,
+            epsilon = 1.0e-9
+        );
+        assert_relative_eq!(body.angular_velocity, Vector3::zeros());
+    }
+
+    #[test]
+    fn recentered() {
+        let moved = Polyhedron::cube().map(|p| [p[0] + 3.0, p[1], p[2] - 1.0]);
+        let body = RigidBody::new(&moved, 1.0);
+        assert_relative_eq!(body.position, Point3::new(3.0, 0.0, -1.0), epsilon = 1.0e-6);
+        let props = body.shape.mass_properties();
+        assert_relative_eq!(props.center_of_mass, Point3::origin(), epsilon = 1.0e-6);
+        let corner = body.transform() * body.shape.points[0];
+        assert_relative_eq!(corner, Point3::new(3.5, -0.5, -1.5), epsilon = 1.0e-6);
+    }
+
+    #[test]
+    #[should_panic(expected = "positive density")]
+    fn zero_density() {
+        RigidBody::new(&Polyhedron::cube(), 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "positive density")]
+    fn nan_density() {
+        RigidBody::new(&Polyhedron::cube(), f64::NAN);
+    }
+
+    #[test]
+    #[should_panic(expected = "positive timestep")]
+    fn zero_timestep() {
+        World::new(0.0, Vector3::zeros());
+    }
+
+    #[test]
+    fn spin() {
+        let mut body = RigidBody::new(&Polyhedron::cube(), 1.0);
+        body.apply_torque(&Vector3::new(0.0, 0.0, 1.0));
+        body.step(0.5, &Vector3::zeros());
+        // I = 1/6, so half a second of unit torque leaves it turning at 3 rad/s
+        assert_relative_eq!(
+            body.angular_velocity,
+            Vector3::new(0.0, 0.0, 3.0),
+            epsilon = 1.0e-6
+        );
+        let energy = body.kinetic_energy();
+        for _ in 0..1000 {
+            body.step(0.01, &Vector3::zeros());
+        }
+        assert_relative_eq!(
+            body.angular_velocity,
+            Vector3::new(0.0, 0.0, 3.0),
+            epsilon = 1.0e-6
+        );
+        assert_relative_eq!(body.kinetic_energy(), energy, epsilon = 1.0e-6);
+        let expected = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 1.5 + 30.0);
+        assert!(body.orientation.angle_to(&expected) < 1.0e-9);
+    }
+
+    #[test]
+    fn off_center_force() {
+        let mut body = RigidBody::new(&Polyhedron::cube(), 1.0);
+        let point = Point3::new(0.5, 0.0, 0.0);
+        body.apply_force_at(&Vector3::new(0.0, 1.0, 0.0), &point);
+        body.step(0.1, &Vector3::zeros());
+        assert_relative_eq!(
+            body.linear_velocity,
+            Vector3::new(0.0, 0.1, 0.0),
+            epsilon = 1.0e-9
+        );
+        assert!(body.angular_velocity.z > 0.0);
+        let mut other = RigidBody::new(&Polyhedron::cube(), 1.0);
+        other.apply_impulse_at(&Vector3::new(0.0, 0.1, 0.0), &point);
+        assert_relative_eq!(
+            other.linear_velocity,
+            body.linear_velocity,
+            epsilon = 1.0e-9
+        );
+        assert_relative_eq!(
+            other.angular_velocity,
+            body.angular_velocity,
+            epsilon = 1.0e-6
+        );
+        assert!(other.velocity_at(&point).y > other.linear_velocity.y);
+    }
+
+    #[test]
+    fn deterministic() {
+        let make = || {
+            let mut world = World::new(0.25, Vector3::new(0.0, -9.81, 0.0));
+            let mut body = RigidBody::new(&Polyhedron::octohedron(), 3.0);
+            body.angular_velocity = Vector3::new(1.0, 2.0, 0.5);
+            world.add(body);
+            world
+        };
+        let (mut a, mut b) = (make(), make());
+        assert_eq!(a.advance(0.625), 2);
+        assert_relative_eq!(a.alpha(), 0.5);
+        assert_eq!(a.advance(0.125), 1);
+        for _ in 0..3 {
+            b.step();
+        }
+        let (a, b) = (&a.bodies[0], &b.bodies[0]);
+        assert_eq!(a.position, b.position);
+        assert_eq!(a.orientation, b.orientation);
+        assert_eq!(a.linear_velocity, b.linear_velocity);
+        assert_eq!(a.angular_velocity, b.angular_velocity);
+    }
+}
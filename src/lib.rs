@@ -1,3 +1,6 @@
+// `Option::is_none_or` and `f64::clamp` need Rust 1.82 and 1.50, so the older forms stay
+#![allow(clippy::unnecessary_map_or, clippy::manual_clamp)]
+
 #[macro_use]
 pub extern crate approx;
 pub extern crate nalgebra as na;
@@ -6,7 +9,10 @@ use na::Point2;
 use std::ops::{Index, IndexMut};
 
 pub mod camera;
+pub mod collision;
+pub mod dynamics;
 pub mod plane;
+pub mod predicates;
 pub mod volume;
 
 #[derive(Copy, Clone, Debug)]
@@ -0,0 +1,228 @@
+use super::*;
+use na::{Point2, RealField, Scalar, Vector2};
+
+/// An axis-aligned rectangle, stored as its minimum and maximum corners.
+///
+/// Anything that takes a rectangle also takes the two opposite corners as a `Line2`, in either
+/// order, through `From`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb2<N: Scalar> {
+    pub min: Point2<N>,
+    pub max: Point2<N>,
+}
+
+impl<N: RealField> Aabb2<N> {
+    /// The rectangle with `a` and `b` as opposite corners.
+    pub fn new(a: &Point2<N>, b: &Point2<N>) -> Self {
+        Aabb2 {
+            min: Point2::new(a.x.min(b.x), a.y.min(b.y)),
+            max: Point2::new(a.x.max(b.x), a.y.max(b.y)),
+        }
+    }
+
+    /// A rectangle inside out, so that it holds nothing and any point or rectangle added to it
+    /// becomes the whole result.
+    pub fn empty() -> Self {
+        Aabb2 {
+            min: Point2::new(N::max_value(), N::max_value()),
+            max: Point2::new(N::min_value(), N::min_value()),
+        }
+    }
+
+    /// The smallest rectangle holding every point, or `None` if there are none.
+    pub fn from_points<'a, I: IntoIterator<Item = &'a Point2<N>>>(points: I) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = *points.next()?;
+        Some(points.fold(
+            Aabb2 {
+                min: first,
+                max: first,
+            },
+            |b, p| b.include(p),
+        ))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y
+    }
+
+    pub fn center(&self) -> Point2<N> {
+        na::center(&self.min, &self.max)
+    }
+
+    pub fn size(&self) -> Vector2<N> {
+        self.max - self.min
+    }
+
+    pub fn area(&self) -> N {
+        if self.is_empty() {
+            N::zero()
+        } else {
+            let size = self.size();
+            size.x * size.y
+        }
+    }
+
+    /// The corners, counter-clockwise from `min`.
+    pub fn vertices(&self) -> [Point2<N>; 4] {
+        [
+            self.min,
+            Point2::new(self.max.x, self.min.y),
+            self.max,
+            Point2::new(self.min.x, self.max.y),
+        ]
+    }
+
+    /// The sides, counter-clockwise from the bottom one.
+    pub fn edges(&self) -> [Line2<N>; 4] {
+        let verts = self.vertices();
+        [
+            [verts[0], verts[1]],
+            [verts[1], verts[2]],
+            [verts[2], verts[3]],
+            [verts[3], verts[0]],
+        ]
+    }
+
+    /// Whether the point is inside or on the boundary.
+    pub fn contains_point(&self, p: &Point2<N>) -> bool {
+        (0..2).all(|i| self.min[i] <= p[i] && p[i] <= self.max[i])
+    }
+
+    /// Whether `other` lies entirely inside this rectangle, boundary included.
+    pub fn contains(&self, other: &Aabb2<N>) -> bool {
+        (0..2).all(|i| self.min[i] <= other.min[i] && other.max[i] <= self.max[i])
+    }
+
+    /// Whether the rectangles share any point, including when one contains the other or they
+    /// only touch.
+    pub fn overlaps(&self, other: &Aabb2<N>) -> bool {
+        (0..2).all(|i| self.min[i] <= other.max[i] && other.min[i] <= self.max[i])
+    }
+
+    /// The smallest rectangle holding both.
+    pub fn union(&self, other: &Aabb2<N>) -> Self {
+        Aabb2 {
+            min: Point2::from(self.min.coords.zip_map(&other.min.coords, |a, b| a.min(b))),
+            max: Point2::from(self.max.coords.zip_map(&other.max.coords, |a, b| a.max(b))),
+        }
+    }
+
+    /// The area both rectangles cover, or `None` if they don't overlap. Rectangles that only
+    /// touch give one with no width or height.
+    pub fn intersection(&self, other: &Aabb2<N>) -> Option<Self> {
+        if !self.overlaps(other) {
+            return None;
+        }
+        Some(Aabb2 {
+            min: Point2::from(self.min.coords.zip_map(&other.min.coords, |a, b| a.max(b))),
+            max: Point2::from(self.max.coords.zip_map(&other.max.coords, |a, b| a.min(b))),
+        })
+    }
+
+    /// The smallest rectangle holding this one and `p`.
+    pub fn include(&self, p: &Point2<N>) -> Self {
+        Aabb2 {
+            min: Point2::from(self.min.coords.zip_map(&p.coords, |a, b| a.min(b))),
+            max: Point2::from(self.max.coords.zip_map(&p.coords, |a, b| a.max(b))),
+        }
+    }
+
+    /// Grows every side outwards by `margin`, or shrinks them if it's negative.
+    pub fn expand(&self, margin: N) -> Self {
+        let margin = Vector2::new(margin, margin);
+        Aabb2 {
+            min: self.min - margin,
+            max: self.max + margin,
+        }
+    }
+
+    /// The point of the rectangle nearest `p`, which is `p` itself when it's inside.
+    pub fn closest_point(&self, p: &Point2<N>) -> Point2<N> {
+        Point2::from(
+            p.coords
+                .zip_map(&self.min.coords, |a, b| a.max(b))
+                .zip_map(&self.max.coords, |a, b| a.min(b)),
+        )
+    }
+
+    pub fn distance(&self, p: &Point2<N>) -> N {
+        na::distance(p, &self.closest_point(p))
+    }
+}
+
+impl<N: RealField> From<Line2<N>> for Aabb2<N> {
+    fn from(corners: Line2<N>) -> Self {
+        Aabb2::new(&corners[0], &corners[1])
+    }
+}
+
+impl<'a, N: RealField> From<&'a Line2<N>> for Aabb2<N> {
+    fn from(corners: &'a Line2<N>) -> Self {
+        Aabb2::new(&corners[0], &corners[1])
+    }
+}
+
+impl<'a, N: RealField> From<&'a Aabb2<N>> for Aabb2<N> {
+    fn from(aabb: &'a Aabb2<N>) -> Self {
+        *aabb
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn aabb(a: [f64; 2], b: [f64; 2]) -> Aabb2<f64> {
+        Aabb2::new(&a.into(), &b.into())
+    }
+
+    #[test]
+    fn nested() {
+        let outer = aabb([2.0, 2.0], [-2.0, -2.0]);
+        let inner = aabb([-0.5, 0.0], [0.5, 1.0]);
+        assert_eq!(outer.min, Point2::new(-2.0, -2.0));
+        // no edges cross, but they still overlap
+        assert!(aabb_collision(outer, inner).is_empty());
+        assert!(outer.overlaps(&inner) && inner.overlaps(&outer));
+        assert!(outer.contains(&inner) && !inner.contains(&outer));
+        assert_eq!(outer.intersection(&inner), Some(inner));
+        assert_eq!(outer.union(&inner), outer);
+    }
+
+    #[test]
+    fn disjoint() {
+        let a = aabb([0.0, 0.0], [1.0, 1.0]);
+        let b = aabb([2.0, 0.5], [3.0, 3.0]);
+        assert!(!a.overlaps(&b));
+        assert!(a.intersection(&b).is_none());
+        assert_eq!(a.union(&b), aabb([0.0, 0.0], [3.0, 3.0]));
+        let touching = aabb([1.0, 0.5], [2.0, 2.0]);
+        assert_relative_eq!(a.intersection(&touching).unwrap().area(), 0.0);
+        assert!(a.expand(1.0).overlaps(&b));
+        assert!(!a.expand(-0.25).overlaps(&touching));
+    }
+
+    #[test]
+    fn points() {
+        let pts = [
+            Point2::new(1.0, 3.0),
+            Point2::new(-1.0, 0.5),
+            Point2::new(2.0, 1.0),
+        ];
+        let b = Aabb2::from_points(&pts).unwrap();
+        assert_eq!(b, aabb([-1.0, 0.5], [2.0, 3.0]));
+        assert!(pts.iter().all(|p| b.contains_point(p)));
+        assert!(Aabb2::<f64>::from_points(&[]).is_none());
+        assert!(Aabb2::<f64>::empty().is_empty());
+        assert_eq!(Aabb2::empty().include(&pts[0]).min, pts[0]);
+        assert_relative_eq!(b.center(), Point2::new(0.5, 1.75));
+        assert_relative_eq!(b.area(), 7.5);
+        assert_relative_eq!(
+            b.closest_point(&Point2::new(0.0, 5.0)),
+            Point2::new(0.0, 3.0)
+        );
+        assert_relative_eq!(b.distance(&Point2::new(5.0, 7.0)), 5.0);
+        assert_relative_eq!(b.distance(&Point2::new(0.0, 1.0)), 0.0);
+    }
+}
@@ -0,0 +1,456 @@
+use super::multipolygon::*;
+use super::polygon::*;
+use super::*;
+use na::{Point2, Vector2};
+use std::collections::{HashMap, HashSet};
+
+/// Vertices closer than this are merged when building the combined edge graph.
+const EPSILON: f64 = 1.0e-9;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BooleanOp {
+    Union,
+    Intersection,
+    Difference,
+    Xor,
+}
+
+impl Polygon<f64> {
+    /// Combines two simple polygons (convex or concave, of either winding).
+    ///
+    /// Both polygons' edges are split wherever they cross or overlap the other's, each piece is
+    /// classified as inside, outside, or shared with the other polygon, and the pieces the
+    /// operation keeps are linked back into rings. Outer rings are returned counter-clockwise and
+    /// holes clockwise, each outer ring followed by the holes inside it, with outer rings ordered
+    /// from smallest to largest; rings that only touch at a vertex are returned separately.
+    pub fn boolean(&self, other: &Polygon<f64>, op: BooleanOp) -> Vec<Polygon<f64>> {
+        self.boolean_with_holes(other, op)
+            .polygons
+            .into_iter()
+            .flat_map(|p| std::iter::once(p.outer).chain(p.holes))
+            .collect()
+    }
+
+    /// Like `boolean`, but with each outer ring already grouped with its holes.
+    pub fn boolean_with_holes(&self, other: &Polygon<f64>, op: BooleanOp) -> MultiPolygon<f64> {
+        let a = self.clone().non_degenerate().map(ccw);
+        let b = other.clone().non_degenerate().map(ccw);
+        let rings = match (a, b) {
+            (Some(a), Some(b)) => match op {
+                BooleanOp::Xor => {
+                    let mut res = combine(&a, &b, BooleanOp::Difference);
+                    res.append(&mut combine(&b, &a, BooleanOp::Difference));
+                    res
+                }
+                op => combine(&a, &b, op),
+            },
+            (Some(a), None) => match op {
+                BooleanOp::Intersection => Vec::new(),
+                _ => vec![a],
+            },
+            (None, Some(b)) => match op {
+                BooleanOp::Union | BooleanOp::Xor => vec![b],
+                _ => Vec::new(),
+            },
+            (None, None) => Vec::new(),
+        };
+        MultiPolygon::from_rings(rings)
+    }
+
+    pub fn union(&self, other: &Polygon<f64>) -> Vec<Polygon<f64>> {
+        self.boolean(other, BooleanOp::Union)
+    }
+
+    pub fn intersection(&self, other: &Polygon<f64>) -> Vec<Polygon<f64>> {
+        self.boolean(other, BooleanOp::Intersection)
+    }
+
+    pub fn difference(&self, other: &Polygon<f64>) -> Vec<Polygon<f64>> {
+        self.boolean(other, BooleanOp::Difference)
+    }
+
+    pub fn xor(&self, other: &Polygon<f64>) -> Vec<Polygon<f64>> {
+        self.boolean(other, BooleanOp::Xor)
+    }
+}
+
+fn cross(a: &Vector2<f64>, b: &Vector2<f64>) -> f64 {
+    a[0] * b[1] - a[1] * b[0]
+}
+
+fn ccw(mut poly: Polygon<f64>) -> Polygon<f64> {
+    poly.set_winding(Winding::CounterClockwise);
+    poly
+}
+
+/// The split-up edges of both input polygons, sharing one vertex list.
+struct Graph {
+    verts: Vec<Point2<f64>>,
+    edges: [Vec<(usize, usize)>; 2],
+    /// Vertex ids by grid cell, with cells wide enough that any two vertices `vertex` would merge
+    /// are in the same or neighbouring cells.
+    grid: HashMap<(i64, i64), Vec<usize>>,
+    cell: f64,
+}
+
+impl Graph {
+    fn new(a: &Polygon<f64>, b: &Polygon<f64>) -> Self {
+        // every vertex lies on an input edge, so none is further out than the input points, and
+        // the merge tolerance is relative for coordinates past 1
+        let scale = a
+            .points
+            .iter()
+            .chain(&b.points)
+            .fold(1.0, |m: f64, p| m.max(p.x.abs()).max(p.y.abs()));
+        let mut graph = Graph {
+            verts: Vec::new(),
+            edges: [Vec::new(), Vec::new()],
+            grid: HashMap::new(),
+            cell: EPSILON * scale,
+        };
+        let ids = [
+            a.points.iter().map(|p| graph.vertex(p)).collect::<Vec<_>>(),
+            b.points.iter().map(|p| graph.vertex(p)).collect::<Vec<_>>(),
+        ];
+        // (parameter along the edge, vertex id) for every point where each edge must be split
+        let mut splits = [
+            ids[0]
+                .iter()
+                .enumerate()
+                .map(|(i, &v)| vec![(0.0, v), (1.0, ids[0][(i + 1) % ids[0].len()])])
+                .collect::<Vec<_>>(),
+            ids[1]
+                .iter()
+                .enumerate()
+                .map(|(i, &v)| vec![(0.0, v), (1.0, ids[1][(i + 1) % ids[1].len()])])
+                .collect::<Vec<_>>(),
+        ];
+        for (i, ea) in a.edges().enumerate() {
+            for (j, eb) in b.edges().enumerate() {
+                let points = match line_intersection(&ea, &eb) {
+                    Some(Either::A(p)) => vec![p],
+                    Some(Either::B(l)) => vec![l[0], l[1]],
+                    None => continue,
+                };
+                for p in points {
+                    let v = graph.vertex(&p);
+                    splits[0][i].push((param(&ea, &p), v));
+                    splits[1][j].push((param(&eb, &p), v));
+                }
+            }
+        }
+        for (poly, splits) in splits.iter_mut().enumerate() {
+            for split in splits.iter_mut() {
+                split.sort_by(|l, r| l.0.partial_cmp(&r.0).unwrap());
+                split.dedup_by_key(|s| s.1);
+                for pair in split.windows(2) {
+                    if pair[0].1 != pair[1].1 {
+                        graph.edges[poly].push((pair[0].1, pair[1].1));
+                    }
+                }
+            }
+        }
+        graph
+    }
+
+    /// Finds or inserts a vertex.
+    fn vertex(&mut self, p: &Point2<f64>) -> usize {
+        let key = (
+            (p.x / self.cell).floor() as i64,
+            (p.y / self.cell).floor() as i64,
+        );
+        let mut found = None;
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                let ids = match self.grid.get(&(key.0 + dx, key.1 + dy)) {
+                    Some(ids) => ids,
+                    None => continue,
+                };
+                for &i in ids {
+                    let v = self.verts[i];
+                    // the earliest match wins, as it would in a scan of `verts`
+                    if found.map_or(true, |f| i < f)
+                        && relative_eq!(v, *p, epsilon = EPSILON, max_relative = EPSILON)
+                    {
+                        found = Some(i);
+                    }
+                }
+            }
+        }
+        found.unwrap_or_else(|| {
+            self.verts.push(*p);
+            let i = self.verts.len() - 1;
+            self.grid.entry(key).or_default().push(i);
+            i
+        })
+    }
+
+    fn midpoint(&self, edge: (usize, usize)) -> Point2<f64> {
+        na::center(&self.verts[edge.0], &self.verts[edge.1])
+    }
+
+    /// Links directed edges into closed rings.
+    ///
+    /// Where several edges leave a vertex, the walk takes the sharpest left turn, which keeps each
+    /// ring simple and splits rings that touch at a single vertex.
+    fn link(&self, edges: &[(usize, usize)]) -> Vec<Polygon<f64>> {
+        let mut outgoing = HashMap::<usize, Vec<usize>>::new();
+        for (i, e) in edges.iter().enumerate() {
+            outgoing.entry(e.0).or_default().push(i);
+        }
+        let mut used = vec![false; edges.len()];
+        let mut res = Vec::new();
+        for first in 0..edges.len() {
+            if used[first] {
+                continue;
+            }
+            used[first] = true;
+            let start = edges[first].0;
+            let mut ring = vec![start];
+            let mut cur = edges[first];
+            let closed = loop {
+                if cur.1 == start {
+                    break true;
+                }
+                ring.push(cur.1);
+                let din = self.verts[cur.1] - self.verts[cur.0];
+                let next = outgoing
+                    .get(&cur.1)
+                    .into_iter()
+                    .flatten()
+                    .filter(|&&i| !used[i])
+                    .map(|&i| {
+                        let dout = self.verts[edges[i].1] - self.verts[edges[i].0];
+                        let mut angle = cross(&din, &dout).atan2(din.dot(&dout));
+                        if relative_eq!(angle.abs(), std::f64::consts::PI) {
+                            angle = -angle.abs();
+                        }
+                        (i, angle)
+                    })
+                    .max_by(|l, r| l.1.partial_cmp(&r.1).unwrap());
+                match next {
+                    Some((i, _)) => {
+                        used[i] = true;
+                        cur = edges[i];
+                    }
+                    None => break false,
+                }
+            };
+            if !closed {
+                continue;
+            }
+            let poly = Polygon {
+                points: ring.into_iter().map(|v| self.verts[v]).collect(),
+            };
+            if let Some(poly) = simplify(poly) {
+                res.push(poly);
+            }
+        }
+        res
+    }
+}
+
+/// Parameter of a point known to lie on a segment, measured along the segment.
+fn param(edge: &Line2<f64>, p: &Point2<f64>) -> f64 {
+    let d = edge[1] - edge[0];
+    (p - edge[0]).dot(&d) / d.dot(&d)
+}
+
+/// Drops vertices left in the middle of straight runs by edge splitting, then degenerate rings.
+fn simplify(mut poly: Polygon<f64>) -> Option<Polygon<f64>> {
+    let mut i = 0;
+    while poly.points.len() > 2 && i < poly.points.len() {
+        let len = poly.points.len();
+        let prev = poly[(i + len - 1) % len];
+        let next = poly[(i + 1) % len];
+        let (d0, d1) = (poly[i] - prev, next - poly[i]);
+        if relative_eq!(
+            cross(&d0, &d1),
+            0.0,
+            epsilon = EPSILON * d0.norm() * d1.norm()
+        ) && d0.dot(&d1) > 0.0
+        {
+            poly.points.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+    poly.non_degenerate()
+}
+
+/// Runs a union, intersection or difference on two counter-clockwise polygons.
+fn combine(a: &Polygon<f64>, b: &Polygon<f64>, op: BooleanOp) -> Vec<Polygon<f64>> {
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    enum Class {
+        Inside,
+        Outside,
+        Shared,
+        SharedOpposite,
+    }
+    let graph = Graph::new(a, b);
+    let classify = |edges: &[(usize, usize)], others: &[(usize, usize)], other: &Polygon<f64>| {
+        let others = others.iter().cloned().collect::<HashSet<_>>();
+        edges
+            .iter()
+            .map(|&e| {
+                if others.contains(&e) {
+                    Class::Shared
+                } else if others.contains(&(e.1, e.0)) {
+                    Class::SharedOpposite
+                } else if other.contains(&graph.midpoint(e), FillRule::EvenOdd) {
+                    Class::Inside
+                } else {
+                    Class::Outside
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+    let class_a = classify(&graph.edges[0], &graph.edges[1], b);
+    let class_b = classify(&graph.edges[1], &graph.edges[0], a);
+    let (keep_a, keep_b, reverse_b) = match op {
+        BooleanOp::Union => ([Class::Outside, Class::Shared], Class::Outside, false),
+        BooleanOp::Intersection => ([Class::Inside, Class::Shared], Class::Inside, false),
+        BooleanOp::Difference => ([Class::Outside, Class::SharedOpposite], Class::Inside, true),
+        BooleanOp::Xor => unreachable!("xor is built from two differences"),
+    };
+    let mut edges = Vec::new();
+    for (e, class) in graph.edges[0].iter().zip(&class_a) {
+        if keep_a.contains(class) {
+            edges.push(*e);
+        }
+    }
+    for (e, class) in graph.edges[1].iter().zip(&class_b) {
+        if *class == keep_b {
+            edges.push(if reverse_b { (e.1, e.0) } else { *e });
+        }
+    }
+    graph.link(&edges)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn total_area(polys: &[Polygon<f64>]) -> f64 {
+        polys.iter().map(Polygon::signed_area).sum()
+    }
+
+    #[test]
+    fn overlapping_squares() {
+        let a = Polygon::square(1.0, 0.0, 0.0);
+        let b = Polygon::square(1.0, 1.0, 1.0);
+        assert_relative_eq!(total_area(&a.union(&b)), 7.0);
+        assert_relative_eq!(total_area(&a.intersection(&b)), 1.0);
+        assert_relative_eq!(total_area(&a.difference(&b)), 3.0);
+        assert_relative_eq!(total_area(&a.xor(&b)), 6.0);
+        assert_eq!(a.union(&b)[0].points.len(), 8);
+    }
+
+    #[test]
+    fn shared_edge() {
+        let a = Polygon::square(1.0, 0.0, 0.0);
+        let b = Polygon::square(1.0, 2.0, 0.0);
+        let union = a.union(&b);
+        assert_eq!(union.len(), 1);
+        assert_eq!(union[0].points.len(), 4);
+        assert_relative_eq!(total_area(&union), 8.0);
+        assert!(a.intersection(&b).is_empty());
+        assert_relative_eq!(total_area(&a.difference(&b)), 4.0);
+        assert_relative_eq!(total_area(&a.xor(&b)), 8.0);
+    }
+
+    #[test]
+    fn partially_shared_edge() {
+        let a = Polygon::square(1.0, 0.0, 0.0);
+        let b = Polygon::square(0.5, 1.5, 0.5);
+        let union = a.union(&b);
+        assert_eq!(union.len(), 1);
+        assert_eq!(union[0].points.len(), 6);
+        assert_relative_eq!(total_area(&union), 5.0);
+        assert_relative_eq!(total_area(&a.difference(&b)), 4.0);
+    }
+
+    #[test]
+    fn touching_corner() {
+        let a = Polygon::square(1.0, 0.0, 0.0);
+        let b = Polygon::square(1.0, 2.0, 2.0);
+        let union = a.union(&b);
+        assert_eq!(union.len(), 2);
+        assert_relative_eq!(total_area(&union), 8.0);
+        assert!(a.intersection(&b).is_empty());
+    }
+
+    #[test]
+    fn contained() {
+        let a = Polygon::square(2.0, 0.0, 0.0);
+        let b = Polygon::square(1.0, 0.0, 0.0);
+        assert_relative_eq!(total_area(&a.union(&b)), 16.0);
+        assert_relative_eq!(total_area(&a.intersection(&b)), 4.0);
+        let diff = a.difference(&b);
+        assert_eq!(diff.len(), 2);
+        assert_eq!(diff[0].winding(), Some(Winding::CounterClockwise));
+        assert_eq!(diff[1].winding(), Some(Winding::Clockwise));
+        assert_relative_eq!(total_area(&diff), 12.0);
+        let grouped = a.boolean_with_holes(&b, BooleanOp::Difference);
+        assert_eq!(grouped.polygons.len(), 1);
+        assert_eq!(grouped.polygons[0].holes.len(), 1);
+        assert_relative_eq!(grouped.area(), 12.0);
+        assert!(b.difference(&a).is_empty());
+    }
+
+    #[test]
+    fn identical() {
+        let a = Polygon::square(1.0, 0.0, 0.0);
+        let mut b = a.clone();
+        b.points.reverse();
+        assert_relative_eq!(total_area(&a.union(&b)), 4.0);
+        assert_relative_eq!(total_area(&a.intersection(&b)), 4.0);
+        assert!(a.difference(&b).is_empty());
+        assert!(a.xor(&b).is_empty());
+    }
+
+    #[test]
+    fn merged_vertices() {
+        // far enough out that the merge tolerance is relative, not absolute
+        let a = Polygon::square(1.0, 1.0e4, 0.0);
+        let mut graph = Graph::new(&a, &a);
+        assert_eq!(graph.verts.len(), 4);
+        for (i, p) in a.points.iter().enumerate() {
+            assert_eq!(graph.vertex(&(p + Vector2::new(5.0e-6, -5.0e-10))), i);
+        }
+        assert_eq!(graph.vertex(&Point2::new(1.0e4, 0.0)), 4);
+        assert_eq!(graph.vertex(&Point2::new(1.0e4, 1.0e-12)), 4);
+        assert_eq!(graph.vertex(&Point2::new(1.0e4, 1.0e-3)), 5);
+    }
+
+    #[test]
+    fn concave() {
+        // an L covering the square's left and bottom halves
+        let l = Polygon {
+            points: vec![
+                Point2::new(-1.0, -1.0),
+                Point2::new(1.0, -1.0),
+                Point2::new(1.0, 0.0),
+                Point2::new(0.0, 0.0),
+                Point2::new(0.0, 1.0),
+                Point2::new(-1.0, 1.0),
+            ],
+        };
+        let b = Polygon::square(0.5, 0.25, 0.25);
+        assert_relative_eq!(total_area(&l.union(&b)), 3.5625);
+        assert_relative_eq!(total_area(&l.intersection(&b)), 0.4375);
+        assert_relative_eq!(total_area(&b.difference(&l)), 0.5625);
+        assert_relative_eq!(total_area(&l.xor(&b)), 3.125);
+    }
+
+    #[test]
+    fn degenerate() {
+        let a = Polygon::square(1.0, 0.0, 0.0);
+        let line = Polygon {
+            points: vec![Point2::new(-1.0, 0.0), Point2::new(1.0, 0.0)],
+        };
+        assert_relative_eq!(total_area(&a.union(&line)), 4.0);
+        assert!(a.intersection(&line).is_empty());
+        assert!(line.difference(&a).is_empty());
+    }
+}
@@ -0,0 +1,281 @@
+//! Distance and penetration queries between convex shapes in the plane, by GJK and EPA.
+//!
+//! These mirror `volume::gjk`, one dimension down: the simplex tops out at a triangle and the
+//! expanding polytope is a polygon.
+
+use super::aabb::*;
+use super::polygon::*;
+use collision::{self, combine, segment_weight, Gjk, MAX_ITERATIONS, TOLERANCE};
+use na::{Point2, Unit, UnitComplex, Vector2};
+
+type Vert = collision::Vert<Vector2<f64>>;
+
+/// A convex shape, described by its furthest point in any direction.
+pub trait SupportMap {
+    /// A point of the shape as far along `dir` as possible. `dir` need not be normalized.
+    fn support(&self, dir: &Vector2<f64>) -> Point2<f64>;
+}
+
+/// Treats the polygon as its convex hull.
+impl SupportMap for Polygon<f64> {
+    fn support(&self, dir: &Vector2<f64>) -> Point2<f64> {
+        self.points
+            .iter()
+            .max_by(|l, r| l.coords.dot(dir).partial_cmp(&r.coords.dot(dir)).unwrap())
+            .cloned()
+            .unwrap_or_else(Point2::origin)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Circle {
+    pub center: Point2<f64>,
+    pub radius: f64,
+}
+
+impl SupportMap for Circle {
+    fn support(&self, dir: &Vector2<f64>) -> Point2<f64> {
+        let dir = Unit::try_new(*dir, 0.0).unwrap_or_else(Vector2::x_axis);
+        self.center + dir.as_ref() * self.radius
+    }
+}
+
+/// A rectangle, which unlike `Polygon::square` may be turned any way.
+#[derive(Copy, Clone, Debug)]
+pub struct Rect {
+    pub center: Point2<f64>,
+    /// Half the rectangle's size along each of its own axes.
+    pub half_extents: Vector2<f64>,
+    pub rotation: UnitComplex<f64>,
+}
+
+impl SupportMap for Rect {
+    fn support(&self, dir: &Vector2<f64>) -> Point2<f64> {
+        let local = self.rotation.inverse() * dir;
+        let corner = self
+            .half_extents
+            .zip_map(&local, |h, d| if d < 0.0 { -h } else { h });
+        self.center + self.rotation * corner
+    }
+}
+
+impl SupportMap for Aabb2<f64> {
+    fn support(&self, dir: &Vector2<f64>) -> Point2<f64> {
+        Point2::new(
+            if dir.x < 0.0 { self.min.x } else { self.max.x },
+            if dir.y < 0.0 { self.min.y } else { self.max.y },
+        )
+    }
+}
+
+/// The closest points of two shapes that don't overlap; see `collision::Separation`.
+pub type Separation = collision::Separation<Vector2<f64>>;
+
+/// How two shapes overlap; see `collision::Contact`.
+pub type Contact = collision::Contact<Vector2<f64>>;
+
+fn support<A: SupportMap, B: SupportMap>(a: &A, b: &B, dir: &Vector2<f64>) -> Vert {
+    Vert::new(a.support(dir), b.support(&-dir))
+}
+
+/// The barycentric weights of the point of a triangle closest to the origin, or `None` if the
+/// triangle contains it.
+fn closest(simplex: &[Vert]) -> Option<Vec<f64>> {
+    let area = (simplex[1].p - simplex[0].p).perp(&(simplex[2].p - simplex[0].p));
+    let mut best: Option<(f64, Vec<f64>)> = None;
+    let mut inside = true;
+    for &[i, j, k] in &[[0, 1, 2], [1, 2, 0], [2, 0, 1]] {
+        let (p, q) = (simplex[i].p, simplex[j].p);
+        // the origin is past this edge if it's on the side away from the third vertex
+        if (q - p).perp(&-p) * area >= 0.0 {
+            continue;
+        }
+        inside = false;
+        let t = segment_weight(&p, &q);
+        let mut weights = vec![0.0; 3];
+        weights[i] = 1.0 - t;
+        weights[j] = t;
+        weights[k] = 0.0;
+        let d = (p + (q - p) * t).norm_squared();
+        if best.as_ref().map_or(true, |b| d < b.0) {
+            best = Some((d, weights));
+        }
+    }
+    if inside {
+        None
+    } else {
+        best.map(|b| b.1)
+    }
+}
+
+fn gjk<A: SupportMap, B: SupportMap>(a: &A, b: &B) -> Gjk<Vector2<f64>> {
+    collision::gjk(|dir| support(a, b, dir), closest)
+}
+
+/// The closest points of two convex shapes, or `None` if they touch or overlap.
+pub fn distance<A: SupportMap, B: SupportMap>(a: &A, b: &B) -> Option<Separation> {
+    match gjk(a, b) {
+        Gjk::Separated(sep) => Some(sep),
+        Gjk::Overlapping(_) => None,
+    }
+}
+
+pub fn intersects<A: SupportMap, B: SupportMap>(a: &A, b: &B) -> bool {
+    distance(a, b).is_none()
+}
+
+/// How deeply two convex shapes overlap, or `None` if they don't.
+///
+/// Shapes that only touch, or that are too thin to enclose the origin in their Minkowski
+/// difference, overlap with a depth of 0.
+pub fn penetration<A: SupportMap, B: SupportMap>(a: &A, b: &B) -> Option<Contact> {
+    match gjk(a, b) {
+        Gjk::Separated(_) => None,
+        Gjk::Overlapping(simplex) => Some(epa(a, b, simplex)),
+    }
+}
+
+/// Grows a simplex around the origin into a triangle, if the shapes have the area for one.
+fn blow_up<A: SupportMap, B: SupportMap>(a: &A, b: &B, mut simplex: Vec<Vert>) -> Vec<Vert> {
+    let scale = simplex.iter().map(|s| s.p.norm()).fold(1.0, f64::max);
+    let eps = 1.0e-9 * scale;
+    if simplex.len() == 1 {
+        for dir in &[Vector2::x(), -Vector2::x(), Vector2::y(), -Vector2::y()] {
+            let v = support(a, b, dir);
+            if (v.p - simplex[0].p).norm() > eps {
+                simplex.push(v);
+                break;
+            }
+        }
+    }
+    if simplex.len() == 2 {
+        let d = simplex[1].p - simplex[0].p;
+        let n = Vector2::new(-d.y, d.x);
+        for dir in &[n, -n] {
+            let v = support(a, b, dir);
+            if d.perp(&(v.p - simplex[0].p)).abs() > eps * d.norm() {
+                simplex.push(v);
+                break;
+            }
+        }
+    }
+    simplex
+}
+
+/// Expands the simplex around the origin towards the boundary of the Minkowski difference, to
+/// find the edge nearest the origin (the expanding polytope algorithm).
+fn epa<A: SupportMap, B: SupportMap>(a: &A, b: &B, simplex: Vec<Vert>) -> Contact {
+    let mut verts = blow_up(a, b, simplex);
+    if verts.len() < 3 {
+        return Contact {
+            normal: Vector2::x_axis(),
+            depth: 0.0,
+            points: [verts[0].a, verts[0].b],
+        };
+    }
+    // keep the polygon counter-clockwise, so edge normals face out
+    if (verts[1].p - verts[0].p).perp(&(verts[2].p - verts[0].p)) < 0.0 {
+        verts.swap(1, 2);
+    }
+    let mut nearest = (0, Vector2::x_axis(), f64::INFINITY);
+    for _ in 0..MAX_ITERATIONS {
+        nearest = (0, Vector2::x_axis(), f64::INFINITY);
+        for i in 0..verts.len() {
+            let d = verts[(i + 1) % verts.len()].p - verts[i].p;
+            if let Some(n) = Unit::try_new(Vector2::new(d.y, -d.x), 0.0) {
+                let dist = n.dot(&verts[i].p);
+                if dist < nearest.2 {
+                    nearest = (i, n, dist);
+                }
+            }
+        }
+        let (i, n, d) = nearest;
+        let next = support(a, b, &n);
+        if next.p.dot(&n) - d <= TOLERANCE * d.abs().max(1.0) {
+            break;
+        }
+        verts.insert(i + 1, next);
+    }
+    let (i, n, _) = nearest;
+    let (p, q) = (verts[i], verts[(i + 1) % verts.len()]);
+    let t = segment_weight(&p.p, &q.p);
+    let depth = (p.p + (q.p - p.p) * t).dot(&n);
+    Contact {
+        normal: n,
+        depth: depth.max(0.0),
+        points: combine(&[p, q], &[1.0 - t, t]),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::sat::sat_collision;
+    use super::*;
+
+    #[test]
+    fn circles() {
+        let a = Circle {
+            center: Point2::new(0.0, 0.0),
+            radius: 1.0,
+        };
+        let b = Circle {
+            center: Point2::new(3.0, 4.0),
+            radius: 2.0,
+        };
+        let sep = distance(&a, &b).unwrap();
+        assert_relative_eq!(sep.distance, 2.0, epsilon = 1.0e-6);
+        assert_relative_eq!(sep.points[0], Point2::new(0.6, 0.8), epsilon = 1.0e-3);
+        assert_relative_eq!(sep.points[1], Point2::new(1.8, 2.4), epsilon = 1.0e-3);
+        let c = Circle {
+            center: Point2::new(0.0, 2.5),
+            radius: 2.0,
+        };
+        assert!(intersects(&a, &c));
+        let contact = penetration(&a, &c).unwrap();
+        assert_relative_eq!(contact.depth, 0.5, epsilon = 1.0e-3);
+        assert_relative_eq!(contact.normal.into_inner(), Vector2::y(), epsilon = 1.0e-2);
+    }
+
+    #[test]
+    fn squares() {
+        let a = Polygon::square(0.5, 0.0, 0.0);
+        let sep = distance(&a, &Polygon::square(0.5, 2.0, 0.25)).unwrap();
+        assert_relative_eq!(sep.distance, 1.0, epsilon = 1.0e-9);
+        assert_relative_eq!(sep.points[0].x, 0.5, epsilon = 1.0e-9);
+        assert_relative_eq!(sep.points[1].x, 1.5, epsilon = 1.0e-9);
+        let b = Polygon::square(0.5, 0.2, 0.8);
+        let contact = penetration(&a, &b).unwrap();
+        let sat = sat_collision(&a, &b).unwrap();
+        assert_relative_eq!(contact.depth, sat.depth, epsilon = 1.0e-9);
+        assert_relative_eq!(
+            contact.normal.into_inner(),
+            sat.axis.into_inner(),
+            epsilon = 1.0e-9
+        );
+        assert_relative_eq!(contact.points[0].y, 0.5, epsilon = 1.0e-9);
+        assert_relative_eq!(contact.points[1].y, 0.3, epsilon = 1.0e-9);
+        let touching = penetration(&a, &Polygon::square(0.5, 1.0, 0.0)).unwrap();
+        assert_relative_eq!(touching.depth, 0.0, epsilon = 1.0e-9);
+    }
+
+    #[test]
+    fn rect() {
+        let turned = Rect {
+            center: Point2::new(2.0, 0.0),
+            half_extents: Vector2::new(0.5, 0.5),
+            rotation: UnitComplex::new(std::f64::consts::FRAC_PI_4),
+        };
+        // a corner points back at the square
+        let sep = distance(&Polygon::square(0.5, 0.0, 0.0), &turned).unwrap();
+        assert_relative_eq!(sep.distance, 1.5 - 0.5f64.sqrt(), epsilon = 1.0e-9);
+        let aabb = Polygon::square(0.5, 0.0, 0.0).aabb().unwrap();
+        let sep = distance(&aabb, &turned).unwrap();
+        assert_relative_eq!(sep.distance, 1.5 - 0.5f64.sqrt(), epsilon = 1.0e-9);
+        let circle = Circle {
+            center: Point2::new(2.0, 0.0),
+            radius: 1.0,
+        };
+        let contact = penetration(&turned, &circle).unwrap();
+        assert_relative_eq!(contact.depth, 1.0 + 0.5, epsilon = 1.0e-3);
+    }
+}
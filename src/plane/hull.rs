@@ -0,0 +1,155 @@
+use super::orient;
+use super::polygon::*;
+use na::Point2;
+
+/// What to do with input points that lie on the hull's edges rather than at its corners.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Collinear {
+    /// Only keep the corners.
+    Exclude,
+    /// Keep every input point on the boundary.
+    Include,
+}
+
+/// Computes the convex hull of a set of points (Andrew's monotone chain).
+///
+/// The hull is returned counter-clockwise, starting from its lowest-leftmost point, without
+/// duplicate points. Collinearity is decided by `orient`, so it is exact with the `robust` feature
+/// and tolerant otherwise. If every point is collinear, the result is the
+/// degenerate polygon running along that line (its two ends, or every point in order with
+/// `Collinear::Include`).
+pub fn convex_hull<I: IntoIterator<Item = Point2<f64>>>(
+    points: I,
+    collinear: Collinear,
+) -> Polygon<f64> {
+    let mut points = points.into_iter().collect::<Vec<_>>();
+    points.sort_by(|l, r| (l.x, l.y).partial_cmp(&(r.x, r.y)).unwrap());
+    points.dedup_by(|a, b| relative_eq!(*a, *b));
+    if points.len() < 3 {
+        return Polygon { points };
+    }
+    let first = points[0];
+    let last = points[points.len() - 1];
+    if points.iter().all(|p| orient(&first, &last, p) == 0.0) {
+        return match collinear {
+            Collinear::Exclude => Polygon {
+                points: vec![first, last],
+            },
+            Collinear::Include => Polygon { points },
+        };
+    }
+    let keep = |t: f64| match collinear {
+        Collinear::Exclude => t > 0.0,
+        Collinear::Include => t >= 0.0,
+    };
+    let mut hull: Vec<Point2<f64>> = Vec::with_capacity(points.len() + 1);
+    let extend = |hull: &mut Vec<Point2<f64>>, floor: usize, p: &Point2<f64>| {
+        while hull.len() >= floor + 2
+            && !keep(orient(&hull[hull.len() - 2], &hull[hull.len() - 1], p))
+        {
+            hull.pop();
+        }
+        hull.push(*p);
+    };
+    for p in &points {
+        extend(&mut hull, 0, p);
+    }
+    // the upper chain starts where the lower one ends, and mustn't eat into it
+    let floor = hull.len() - 1;
+    for p in points.iter().rev().skip(1) {
+        extend(&mut hull, floor, p);
+    }
+    hull.pop();
+    Polygon { points: hull }
+}
+
+impl Polygon<f64> {
+    /// The convex hull of this polygon's vertices; see `convex_hull`.
+    pub fn convex_hull(&self, collinear: Collinear) -> Polygon<f64> {
+        convex_hull(self.points.iter().cloned(), collinear)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn grid() -> Vec<Point2<f64>> {
+        let mut res = Vec::new();
+        for x in -2..=2 {
+            for y in -1..=1 {
+                res.push(Point2::new(x as f64, y as f64));
+            }
+        }
+        res
+    }
+
+    #[test]
+    fn exclude() {
+        let hull = convex_hull(grid(), Collinear::Exclude);
+        assert_eq!(
+            hull.points,
+            vec![
+                Point2::new(-2.0, -1.0),
+                Point2::new(2.0, -1.0),
+                Point2::new(2.0, 1.0),
+                Point2::new(-2.0, 1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn include() {
+        let hull = convex_hull(grid(), Collinear::Include);
+        assert_eq!(hull.points.len(), 12);
+        assert_eq!(hull.winding(), Some(Winding::CounterClockwise));
+        assert_relative_eq!(hull.area(), 8.0);
+        for edge in hull.edges() {
+            assert_relative_eq!(na::distance(&edge[0], &edge[1]), 1.0);
+        }
+    }
+
+    #[test]
+    fn concave_polygon() {
+        let l = Polygon {
+            points: vec![
+                Point2::new(0.0, 0.0),
+                Point2::new(2.0, 0.0),
+                Point2::new(2.0, 1.0),
+                Point2::new(1.0, 1.0),
+                Point2::new(1.0, 2.0),
+                Point2::new(0.0, 2.0),
+            ],
+        };
+        let hull = l.convex_hull(Collinear::Exclude);
+        assert_eq!(hull.points.len(), 5);
+        assert_relative_eq!(hull.area(), 3.5);
+    }
+
+    #[test]
+    fn duplicates() {
+        let mut points = Polygon::square(1.0, 0.0, 0.0).points;
+        points.extend(points.clone());
+        let hull = convex_hull(points, Collinear::Include);
+        assert_eq!(hull.points.len(), 4);
+    }
+
+    #[test]
+    fn degenerate() {
+        let line = (0..5).map(|i| Point2::new(i as f64, i as f64 * 2.0));
+        assert_eq!(
+            convex_hull(line.clone(), Collinear::Exclude).points.len(),
+            2
+        );
+        assert_eq!(convex_hull(line, Collinear::Include).points.len(), 5);
+        assert_eq!(
+            convex_hull(vec![Point2::new(1.0, 1.0)], Collinear::Include)
+                .points
+                .len(),
+            1
+        );
+        assert!(convex_hull(Vec::new(), Collinear::Exclude)
+            .points
+            .is_empty());
+    }
+}
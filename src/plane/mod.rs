@@ -1,10 +1,42 @@
+use self::aabb::*;
 use super::*;
 use na::{Point2, Scalar, Vector2};
 
+pub mod aabb;
+pub mod boolean;
+pub mod gjk;
+pub mod hull;
+pub mod multipolygon;
 pub mod polygon;
+pub mod sat;
+pub mod triangulate;
 
 pub type Line2<N> = [Point2<N>; 2];
 
+/// Twice the signed area of the triangle `abc`: positive when it winds counter-clockwise, negative
+/// when clockwise, and zero when the points are collinear.
+///
+/// With the `robust` feature the sign is exact (see `predicates::orient2d`). Otherwise this is a
+/// plain cross product, snapped to zero when within `relative_eq!` tolerance of it relative to the
+/// lengths involved.
+pub fn orient(a: &Point2<f64>, b: &Point2<f64>, c: &Point2<f64>) -> f64 {
+    if cfg!(feature = "robust") {
+        return super::predicates::orient2d(a, b, c);
+    }
+    let (ab, ac) = (b - a, c - a);
+    let res = ab.perp(&ac);
+    if relative_eq!(res / (ab.norm() * ac.norm()).max(f64::MIN_POSITIVE), 0.0) {
+        0.0
+    } else {
+        res
+    }
+}
+
+/// Intersects two line segments.
+///
+/// Crossing segments produce `Either::A` with the crossing point. Collinear segments that overlap
+/// produce `Either::B` with the shared sub-segment, directed the same way as `a`; if they only
+/// touch at an endpoint, that point is returned as `Either::A` instead.
 pub fn line_intersection(
     a: &[Point2<f64>; 2],
     b: &[Point2<f64>; 2],
@@ -13,26 +45,102 @@ pub fn line_intersection(
     fn cross(a: &Vector2<f64>, b: &Vector2<f64>) -> f64 {
         a[0] * b[1] - a[1] * b[0]
     }
+    if cfg!(feature = "robust") {
+        return exact_line_intersection(a, b);
+    }
     let r = a[1] - a[0];
     let s = b[1] - b[0];
     let rxs = cross(&r, &s);
     let diff = b[0] - a[0];
     if !relative_eq!(rxs, 0.0) {
         let u = cross(&diff, &r) / rxs;
-        if u >= 0.0 && u <= 1.0 {
+        if (0.0..=1.0).contains(&u) {
             let t = cross(&diff, &s) / rxs;
-            if t >= 0.0 && t <= 1.0 {
+            if (0.0..=1.0).contains(&t) {
                 let p = r * t;
                 return Some(A(a[0] + p));
             }
         }
+        return None;
+    }
+    if relative_eq!(cross(&diff, &r), 0.0) && relative_eq!(cross(&diff, &s), 0.0) {
+        return collinear_overlap(a, b);
     }
-    //if relative_eq!(cross(&diff, &r).abs(), 0.0) {
-    //    panic!("Not Implemented: 2D Collinear Line Intersection")
-    //}
     None
 }
 
+/// `line_intersection`, deciding which side of each segment the other's endpoints lie on exactly.
+fn exact_line_intersection(
+    a: &[Point2<f64>; 2],
+    b: &[Point2<f64>; 2],
+) -> Option<Either<Point2<f64>, Line2<f64>>> {
+    use super::predicates::orient2d;
+    use super::Either::*;
+    let (a0, a1) = (orient2d(&b[0], &b[1], &a[0]), orient2d(&b[0], &b[1], &a[1]));
+    let (b0, b1) = (orient2d(&a[0], &a[1], &b[0]), orient2d(&a[0], &a[1], &b[1]));
+    if a0 == 0.0 && a1 == 0.0 && b0 == 0.0 && b1 == 0.0 {
+        return collinear_overlap(a, b);
+    }
+    if a0 * a1 > 0.0 || b0 * b1 > 0.0 {
+        return None;
+    }
+    Some(A(if a0 == 0.0 {
+        a[0]
+    } else if a1 == 0.0 {
+        a[1]
+    } else if b0 == 0.0 {
+        b[0]
+    } else if b1 == 0.0 {
+        b[1]
+    } else {
+        a[0] + (a[1] - a[0]) * (a0 / (a0 - a1))
+    }))
+}
+
+/// Finds the overlap of two segments already known to lie on the same line.
+fn collinear_overlap(
+    a: &[Point2<f64>; 2],
+    b: &[Point2<f64>; 2],
+) -> Option<Either<Point2<f64>, Line2<f64>>> {
+    use super::Either::*;
+    let r = a[1] - a[0];
+    let rr = r.dot(&r);
+    if relative_eq!(rr, 0.0) {
+        // `a` is a single point; it overlaps `b` if it lies within b's extent
+        let s = b[1] - b[0];
+        let ss = s.dot(&s);
+        if relative_eq!(ss, 0.0) {
+            return if relative_eq!(a[0], b[0]) {
+                Some(A(a[0]))
+            } else {
+                None
+            };
+        }
+        let t = (a[0] - b[0]).dot(&s) / ss;
+        return if (0.0..=1.0).contains(&t) {
+            Some(A(a[0]))
+        } else {
+            None
+        };
+    }
+    // project b's endpoints onto a, so that a spans [0, 1]
+    let t0 = (b[0] - a[0]).dot(&r) / rr;
+    let t1 = (b[1] - a[0]).dot(&r) / rr;
+    let lo = t0.min(t1).max(0.0);
+    let hi = t0.max(t1).min(1.0);
+    if lo > hi && !relative_eq!(lo, hi) {
+        return None;
+    }
+    let start = a[0] + r * lo;
+    if relative_eq!(lo, hi) {
+        return Some(A(start));
+    }
+    Some(B([start, a[0] + r * hi]))
+}
+
+/// Intersects a line segment with the infinite line through `inf`.
+///
+/// Returns `Either::B` with the whole segment if it lies on the line.
 pub fn inf_line_intersection(
     a: &[Point2<f64>; 2],
     inf: &[Point2<f64>; 2],
@@ -41,20 +149,36 @@ pub fn inf_line_intersection(
     fn cross(a: &Vector2<f64>, b: &Vector2<f64>) -> f64 {
         a[0] * b[1] - a[1] * b[0]
     }
+    if cfg!(feature = "robust") {
+        let d0 = super::predicates::orient2d(&inf[0], &inf[1], &a[0]);
+        let d1 = super::predicates::orient2d(&inf[0], &inf[1], &a[1]);
+        return if d0 == 0.0 && d1 == 0.0 {
+            Some(B(*a))
+        } else if d0 * d1 > 0.0 {
+            None
+        } else if d0 == 0.0 {
+            Some(A(a[0]))
+        } else if d1 == 0.0 {
+            Some(A(a[1]))
+        } else {
+            Some(A(a[0] + (a[1] - a[0]) * (d0 / (d0 - d1))))
+        };
+    }
     let r = a[1] - a[0];
     let s = inf[1] - inf[0];
     let rxs = cross(&r, &s);
     let diff = inf[0] - a[0];
     if !relative_eq!(rxs, 0.0) {
         let t = cross(&diff, &s) / rxs;
-        if t >= 0.0 && t <= 1.0 {
+        if (0.0..=1.0).contains(&t) {
             let p = r * t;
             return Some(A(a[0] + p));
         }
     }
-    //if relative_eq!(cross(&diff, &r).abs(), 0.0) {
-    //    panic!("Not Implemented: 2D Collinear Line Intersection")
-    //}
+    if relative_eq!(cross(&diff, &s), 0.0) && relative_eq!(cross(&(a[1] - inf[0]), &s), 0.0) {
+        // the whole segment lies on the line
+        return Some(B(*a));
+    }
     None
 }
 
@@ -77,13 +201,20 @@ pub fn rect_to_edges<N: Scalar>(rect: &Line2<N>) -> [Line2<N>; 4] {
     ]
 }
 
-pub fn aabb_collision(r1: &Line2<f64>, r2: &Line2<f64>) -> Vec<Either<Point2<f64>, Line2<f64>>> {
-    let e1 = rect_to_edges(r1);
-    let e2 = rect_to_edges(r2);
+/// Intersects the edges of two axis-aligned rectangles.
+///
+/// Rectangles that contain one another have no edges in common, so this finds nothing for them;
+/// use `Aabb2::overlaps` to test for overlap.
+pub fn aabb_collision<R1: Into<Aabb2<f64>>, R2: Into<Aabb2<f64>>>(
+    r1: R1,
+    r2: R2,
+) -> Vec<Either<Point2<f64>, Line2<f64>>> {
+    let e1 = r1.into().edges();
+    let e2 = r2.into().edges();
     let mut res = Vec::new();
     for edge in e1.iter() {
         for redge in e2.iter() {
-            if let Some(e) = line_intersection(&edge, &redge) {
+            if let Some(e) = line_intersection(edge, redge) {
                 res.push(e)
             }
         }
@@ -91,13 +222,21 @@ pub fn aabb_collision(r1: &Line2<f64>, r2: &Line2<f64>) -> Vec<Either<Point2<f64
     res
 }
 
-pub fn lb_clip(
+pub fn lb_clip<R: Into<Aabb2<f64>>>(
     line: &[Point2<f64>; 2],
-    bound: &[Point2<f64>; 2],
+    bound: R,
 ) -> Option<([Point2<f64>; 2], [bool; 2])> {
+    /// Whether a line with this coordinate delta runs parallel to the bound's edges.
+    fn parallel(p: f64) -> bool {
+        if cfg!(feature = "robust") {
+            p == 0.0
+        } else {
+            relative_eq!(p, 0.0)
+        }
+    }
     fn clip(p: f64, q: f64, mut t: [f64; 2]) -> Option<[f64; 2]> {
         let r = q / p;
-        if relative_eq!(p, 0.0) && q < 0.0 {
+        if parallel(p) && q < 0.0 {
             return None;
         }
         if p < 0.0 {
@@ -117,18 +256,9 @@ pub fn lb_clip(
         }
         Some(t)
     }
-    let mut bound = *bound;
-    // make sure that the line describing the bound is directed up and right
-    if bound[0][0] > bound[1][0] {
-        let temp = bound[1][0];
-        bound[1][0] = bound[0][0];
-        bound[0][0] = temp;
-    }
-    if bound[0][1] > bound[1][1] {
-        let temp = bound[1][1];
-        bound[1][1] = bound[0][1];
-        bound[0][1] = temp;
-    }
+    // the bound's corners come out of `Aabb2` directed up and right
+    let bound = bound.into();
+    let bound = [bound.min, bound.max];
     let delta = line[1] - line[0];
     let mut tmin = 0.0;
     let mut tmax = 1.0;
@@ -138,7 +268,7 @@ pub fn lb_clip(
         let p = -delta.x;
         let q = -(bound[0].x - line[0].x);
         //let r = q / p;
-        if relative_eq!(p, 0.0) && q < 0.0 {
+        if parallel(p) && q < 0.0 {
             return None;
         }
         if p < 0.0 {
@@ -187,3 +317,78 @@ pub fn lb_clip(
     }
     Some((res, clipped))
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn line(a: [f64; 2], b: [f64; 2]) -> Line2<f64> {
+        [a.into(), b.into()]
+    }
+
+    #[test]
+    fn crossing() {
+        match line_intersection(&line([0.0, 0.0], [2.0, 2.0]), &line([0.0, 2.0], [2.0, 0.0])) {
+            Some(Either::A(p)) => assert_relative_eq!(p, Point2::new(1.0, 1.0)),
+            r => panic!("Expected crossing, got {:?}", r),
+        }
+    }
+
+    #[test]
+    fn collinear_overlap() {
+        match line_intersection(&line([0.0, 0.0], [3.0, 0.0]), &line([4.0, 0.0], [1.0, 0.0])) {
+            Some(Either::B(l)) => {
+                assert_relative_eq!(l[0], Point2::new(1.0, 0.0));
+                assert_relative_eq!(l[1], Point2::new(3.0, 0.0));
+            }
+            r => panic!("Expected overlap, got {:?}", r),
+        }
+    }
+
+    #[test]
+    fn collinear_touching() {
+        match line_intersection(&line([0.0, 1.0], [0.0, 2.0]), &line([0.0, 2.0], [0.0, 5.0])) {
+            Some(Either::A(p)) => assert_relative_eq!(p, Point2::new(0.0, 2.0)),
+            r => panic!("Expected touching point, got {:?}", r),
+        }
+    }
+
+    #[test]
+    fn collinear_disjoint() {
+        assert!(
+            line_intersection(&line([0.0, 0.0], [1.0, 0.0]), &line([2.0, 0.0], [3.0, 0.0]))
+                .is_none()
+        );
+        assert!(
+            line_intersection(&line([0.0, 0.0], [1.0, 0.0]), &line([0.0, 1.0], [1.0, 1.0]))
+                .is_none()
+        );
+    }
+
+    #[test]
+    #[allow(clippy::needless_borrows_for_generic_args)]
+    fn aabb_shared_edge() {
+        let r1 = line([0.0, 0.0], [1.0, 1.0]);
+        let r2 = line([1.0, 0.0], [2.0, 1.0]);
+        let res = aabb_collision(&r1, &r2);
+        assert!(res.iter().any(|e| match e {
+            Either::B(l) => relative_eq!(l[0].x, 1.0) && relative_eq!(l[1].x, 1.0),
+            _ => false,
+        }));
+    }
+
+    #[test]
+    fn aabb_shared_edge_aabb() {
+        let r1 = Aabb2::new(&Point2::new(1.0, 1.0), &Point2::new(0.0, 0.0));
+        let r2 = Aabb2::new(&Point2::new(1.0, 0.0), &Point2::new(2.0, 1.0));
+        let res = aabb_collision(r1, r2);
+        assert!(res.iter().any(|e| match e {
+            Either::B(l) => relative_eq!(l[0].x, 1.0) && relative_eq!(l[1].x, 1.0),
+            _ => false,
+        }));
+        assert_eq!(
+            r1.edges()[1],
+            [Point2::new(1.0, 0.0), Point2::new(1.0, 1.0)]
+        );
+    }
+}
@@ -0,0 +1,255 @@
+use super::polygon::*;
+use super::*;
+use na::{Point2, Scalar};
+use std::fmt::{Display, Formatter};
+
+/// A polygon with polygonal holes cut out of it.
+///
+/// Holes are expected to lie inside `outer` and not to overlap each other. Ring winding does not
+/// matter to any of the queries here.
+#[derive(Clone, Debug)]
+pub struct PolygonWithHoles<N: Scalar> {
+    pub outer: Polygon<N>,
+    pub holes: Vec<Polygon<N>>,
+}
+
+/// A set of disjoint polygons, each of which may have holes.
+#[derive(Clone, Debug)]
+pub struct MultiPolygon<N: Scalar> {
+    pub polygons: Vec<PolygonWithHoles<N>>,
+}
+
+impl<N: Scalar> From<Polygon<N>> for PolygonWithHoles<N> {
+    fn from(outer: Polygon<N>) -> Self {
+        PolygonWithHoles {
+            outer,
+            holes: Vec::new(),
+        }
+    }
+}
+
+impl<N: Scalar> From<PolygonWithHoles<N>> for MultiPolygon<N> {
+    fn from(poly: PolygonWithHoles<N>) -> Self {
+        MultiPolygon {
+            polygons: vec![poly],
+        }
+    }
+}
+
+impl<N: Display + Scalar> Display for PolygonWithHoles<N> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.outer)?;
+        for hole in &self.holes {
+            write!(f, " - {}", hole)?;
+        }
+        Ok(())
+    }
+}
+
+impl<N: Scalar> PolygonWithHoles<N> {
+    /// The outer ring, followed by each hole.
+    pub fn rings(&self) -> impl Iterator<Item = &Polygon<N>> {
+        std::iter::once(&self.outer).chain(self.holes.iter())
+    }
+
+    /// The edges of every ring, starting with the outer ring.
+    pub fn edges(&self) -> impl Iterator<Item = [Point2<N>; 2]> + '_ {
+        self.rings().flat_map(Polygon::edges)
+    }
+}
+
+impl PolygonWithHoles<f64> {
+    pub fn area(&self) -> f64 {
+        self.outer.area() - self.holes.iter().map(Polygon::area).sum::<f64>()
+    }
+
+    pub fn contains(&self, p: &Point2<f64>) -> bool {
+        self.outer.contains(p, FillRule::NonZero)
+            && !self.holes.iter().any(|h| h.contains(p, FillRule::NonZero))
+    }
+
+    /// Like `contains`, but reports points on the boundary of any ring as `Containment::Boundary`.
+    pub fn locate(&self, p: &Point2<f64>) -> Containment {
+        let mut res = self.outer.locate(p, FillRule::NonZero);
+        for hole in &self.holes {
+            if res != Containment::Inside {
+                break;
+            }
+            res = match hole.locate(p, FillRule::NonZero) {
+                Containment::Inside => Containment::Outside,
+                Containment::Outside => Containment::Inside,
+                Containment::Boundary => Containment::Boundary,
+            };
+        }
+        res
+    }
+
+    /// Clips every ring to an axis-aligned rectangle; see `Polygon::clip`.
+    ///
+    /// Holes that reach past the bound are clipped along with the outer ring, so they may end up
+    /// touching it.
+    pub fn clip<R: Into<Aabb2<f64>>>(&self, bound: R) -> Option<PolygonWithHoles<f64>> {
+        let bound = bound.into();
+        Some(PolygonWithHoles {
+            outer: self.outer.clip(bound)?,
+            holes: self.holes.iter().filter_map(|h| h.clip(bound)).collect(),
+        })
+    }
+
+    /// Clips every ring to a convex polygon; see `Polygon::clip_convex`.
+    pub fn clip_convex(&self, clipper: &Polygon<f64>) -> Option<PolygonWithHoles<f64>> {
+        Some(PolygonWithHoles {
+            outer: self.outer.clip_convex(clipper)?,
+            holes: self
+                .holes
+                .iter()
+                .filter_map(|h| h.clip_convex(clipper))
+                .collect(),
+        })
+    }
+}
+
+impl<N: Scalar> MultiPolygon<N> {
+    /// Every ring of every polygon.
+    pub fn rings(&self) -> impl Iterator<Item = &Polygon<N>> {
+        self.polygons.iter().flat_map(PolygonWithHoles::rings)
+    }
+
+    /// The edges of every ring of every polygon.
+    pub fn edges(&self) -> impl Iterator<Item = [Point2<N>; 2]> + '_ {
+        self.rings().flat_map(Polygon::edges)
+    }
+}
+
+impl MultiPolygon<f64> {
+    /// Groups loose rings into polygons with holes, such as those returned by `Polygon::boolean`.
+    ///
+    /// Counter-clockwise rings are taken as outer rings and clockwise rings as holes. Each hole is
+    /// assigned to the smallest outer ring that contains it; holes outside every outer ring are
+    /// dropped. Polygons are ordered by the area of their outer ring, smallest first.
+    pub fn from_rings<I: IntoIterator<Item = Polygon<f64>>>(rings: I) -> Self {
+        let (mut outers, holes) = rings
+            .into_iter()
+            .filter_map(Polygon::non_degenerate)
+            .partition::<Vec<_>, _>(|r| r.signed_area() > 0.0);
+        outers.sort_by(|l, r| l.signed_area().partial_cmp(&r.signed_area()).unwrap());
+        let mut polygons = outers
+            .into_iter()
+            .map(PolygonWithHoles::from)
+            .collect::<Vec<_>>();
+        for hole in holes {
+            // hole vertices may touch the outer ring, so only one needs to be strictly inside
+            let parent = polygons.iter_mut().find(|p| {
+                hole.points
+                    .iter()
+                    .any(|v| p.outer.locate(v, FillRule::NonZero) == Containment::Inside)
+            });
+            if let Some(parent) = parent {
+                parent.holes.push(hole);
+            }
+        }
+        MultiPolygon { polygons }
+    }
+
+    pub fn area(&self) -> f64 {
+        self.polygons.iter().map(PolygonWithHoles::area).sum()
+    }
+
+    pub fn contains(&self, p: &Point2<f64>) -> bool {
+        self.polygons.iter().any(|poly| poly.contains(p))
+    }
+
+    /// Clips every polygon to an axis-aligned rectangle, dropping those that are clipped away.
+    pub fn clip<R: Into<Aabb2<f64>>>(&self, bound: R) -> MultiPolygon<f64> {
+        let bound = bound.into();
+        MultiPolygon {
+            polygons: self.polygons.iter().filter_map(|p| p.clip(bound)).collect(),
+        }
+    }
+
+    /// Clips every polygon to a convex polygon, dropping those that are clipped away.
+    pub fn clip_convex(&self, clipper: &Polygon<f64>) -> MultiPolygon<f64> {
+        MultiPolygon {
+            polygons: self
+                .polygons
+                .iter()
+                .filter_map(|p| p.clip_convex(clipper))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn arena() -> PolygonWithHoles<f64> {
+        PolygonWithHoles {
+            outer: Polygon::square(4.0, 0.0, 0.0),
+            holes: vec![
+                Polygon::square(1.0, -2.0, 0.0),
+                Polygon::square(1.0, 2.0, 0.0),
+            ],
+        }
+    }
+
+    #[test]
+    fn area() {
+        assert_relative_eq!(arena().area(), 56.0);
+    }
+
+    #[test]
+    fn contains() {
+        let arena = arena();
+        assert!(arena.contains(&Point2::new(0.0, 0.0)));
+        assert!(!arena.contains(&Point2::new(2.0, 0.5)));
+        assert!(!arena.contains(&Point2::new(5.0, 0.0)));
+        assert_eq!(arena.locate(&Point2::new(0.0, 0.0)), Containment::Inside);
+        assert_eq!(arena.locate(&Point2::new(2.0, 0.5)), Containment::Outside);
+        assert_eq!(arena.locate(&Point2::new(1.0, 0.5)), Containment::Boundary);
+        assert_eq!(arena.locate(&Point2::new(4.0, 4.0)), Containment::Boundary);
+    }
+
+    #[test]
+    fn edges() {
+        assert_eq!(arena().edges().count(), 12);
+    }
+
+    #[test]
+    #[allow(clippy::needless_borrows_for_generic_args)]
+    fn clip() {
+        let res = arena()
+            .clip(&[Point2::new(0.0, -4.0), Point2::new(4.0, 4.0)])
+            .unwrap();
+        assert_eq!(res.holes.len(), 1);
+        assert_relative_eq!(res.area(), 28.0);
+        let res = arena()
+            .clip(&[Point2::new(1.5, -4.0), Point2::new(4.0, 4.0)])
+            .unwrap();
+        assert_relative_eq!(res.area(), 20.0 - 3.0);
+    }
+
+    #[test]
+    fn clip_aabb() {
+        let bound = Aabb2::new(&Point2::new(4.0, 4.0), &Point2::new(0.0, -4.0));
+        let res = arena().clip(bound).unwrap();
+        assert_eq!(res.holes.len(), 1);
+        assert_relative_eq!(res.area(), 28.0);
+        let multi = MultiPolygon {
+            polygons: vec![arena()],
+        };
+        assert_relative_eq!(multi.clip(bound).area(), 28.0);
+    }
+
+    #[test]
+    fn from_rings() {
+        let outer = Polygon::square(2.0, 0.0, 0.0);
+        let hole = Polygon::square(1.0, 0.0, 0.0);
+        let multi = MultiPolygon::from_rings(outer.difference(&hole));
+        assert_eq!(multi.polygons.len(), 1);
+        assert_eq!(multi.polygons[0].holes.len(), 1);
+        assert_relative_eq!(multi.area(), 12.0);
+        assert!(!multi.contains(&Point2::new(0.0, 0.0)));
+        assert!(multi.contains(&Point2::new(1.5, 0.0)));
+    }
+}
@@ -1,6 +1,6 @@
 use super::super::*;
 use super::*;
-use na::{Point2, Scalar, Vector2};
+use na::{Point2, RealField, Scalar, Vector2};
 use std::fmt::{Display, Formatter};
 
 #[derive(Clone, Debug)]
@@ -51,12 +51,11 @@ impl<N: Scalar> IndexMut<usize> for Polygon<N> {
 
 impl<N: Display + Scalar> Display for Polygon<N> {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        write!(f, "[");
+        write!(f, "[")?;
         for edge in self.edges() {
-            write!(f, "[{}, {}], ", edge[0], edge[1]);
+            write!(f, "[{}, {}], ", edge[0], edge[1])?;
         }
-        write!(f, "]");
-        Ok(())
+        write!(f, "]")
     }
 }
 
@@ -66,6 +65,154 @@ impl<N: Scalar> Polygon<N> {
     }
 }
 
+/// Decides which regions of a self-overlapping polygon count as inside it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FillRule {
+    /// Inside where a ray from the point crosses the boundary an odd number of times.
+    EvenOdd,
+    /// Inside wherever the boundary winds around the point at all.
+    NonZero,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Containment {
+    Inside,
+    Outside,
+    Boundary,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Winding {
+    Clockwise,
+    CounterClockwise,
+}
+
+impl<N: RealField> Polygon<N> {
+    /// Area by the shoelace formula; positive when the polygon winds counter-clockwise.
+    pub fn signed_area(&self) -> N {
+        let mut res = N::zero();
+        for edge in self.edges() {
+            res += edge[0].x * edge[1].y - edge[1].x * edge[0].y;
+        }
+        let two: N = na::convert(2.0);
+        res / two
+    }
+
+    pub fn area(&self) -> N {
+        self.signed_area().abs()
+    }
+
+    /// The direction this polygon winds in, or `None` if it has no area.
+    pub fn winding(&self) -> Option<Winding> {
+        let area = self.signed_area();
+        if relative_eq!(area, N::zero()) {
+            None
+        } else if area > N::zero() {
+            Some(Winding::CounterClockwise)
+        } else {
+            Some(Winding::Clockwise)
+        }
+    }
+
+    /// Reverses this polygon if it winds the wrong way. Polygons without area are left alone.
+    pub fn set_winding(&mut self, winding: Winding) {
+        if self.winding().map_or(false, |w| w != winding) {
+            self.points.reverse();
+        }
+    }
+
+    /// The center of mass of this polygon's area, or the average of its vertices if it has none.
+    pub fn centroid(&self) -> Point2<N> {
+        let area = self.signed_area();
+        if relative_eq!(area, N::zero()) {
+            let mut sum = Vector2::zeros();
+            for p in &self.points {
+                sum += p.coords;
+            }
+            let count: N = na::convert(self.points.len().max(1) as f64);
+            return Point2::from(sum / count);
+        }
+        let mut sum = Vector2::zeros();
+        for edge in self.edges() {
+            let cross = edge[0].x * edge[1].y - edge[1].x * edge[0].y;
+            sum += (edge[0].coords + edge[1].coords) * cross;
+        }
+        let six: N = na::convert(6.0);
+        Point2::from(sum / (area * six))
+    }
+
+    pub fn perimeter(&self) -> N {
+        let mut res = N::zero();
+        for edge in self.edges() {
+            res += na::distance(&edge[0], &edge[1]);
+        }
+        res
+    }
+
+    /// How many times this polygon winds counter-clockwise around a point.
+    pub fn winding_number(&self, p: &Point2<N>) -> i32 {
+        let mut res = 0;
+        for edge in self.edges() {
+            let side = (edge[1] - edge[0]).perp(&(p - edge[0]));
+            if edge[0].y <= p.y {
+                if edge[1].y > p.y && side > N::zero() {
+                    res += 1;
+                }
+            } else if edge[1].y <= p.y && side < N::zero() {
+                res -= 1;
+            }
+        }
+        res
+    }
+
+    /// Whether a point is inside this polygon. Points on the boundary may go either way.
+    pub fn contains(&self, p: &Point2<N>, rule: FillRule) -> bool {
+        let winding = self.winding_number(p);
+        match rule {
+            FillRule::EvenOdd => winding % 2 != 0,
+            FillRule::NonZero => winding != 0,
+        }
+    }
+
+    /// Like `contains`, but reports points within `relative_eq!` tolerance of an edge as
+    /// `Containment::Boundary`.
+    pub fn locate(&self, p: &Point2<N>, rule: FillRule) -> Containment {
+        for edge in self.edges() {
+            let delta = edge[1] - edge[0];
+            let len = delta.norm();
+            let diff = p - edge[0];
+            if relative_eq!(len, N::zero()) {
+                if relative_eq!(diff.norm(), N::zero()) {
+                    return Containment::Boundary;
+                }
+                continue;
+            }
+            let along = diff.dot(&delta) / len;
+            if relative_eq!(delta.perp(&diff) / len, N::zero())
+                && (along >= N::zero() || relative_eq!(along, N::zero()))
+                && (along <= len || relative_eq!(along, len))
+            {
+                return Containment::Boundary;
+            }
+        }
+        if self.contains(p, rule) {
+            Containment::Inside
+        } else {
+            Containment::Outside
+        }
+    }
+
+    /// The axis-aligned bounding box of this polygon, as its minimum and maximum corners.
+    pub fn bounds(&self) -> Option<Line2<N>> {
+        self.aabb().map(|b| [b.min, b.max])
+    }
+
+    /// The axis-aligned bounding box of this polygon.
+    pub fn aabb(&self) -> Option<Aabb2<N>> {
+        Aabb2::from_points(&self.points)
+    }
+}
+
 impl Polygon<f64> {
     pub fn square(r: f64, off_x: f64, off_y: f64) -> Polygon<f64> {
         let off = Vector2::new(off_x, off_y);
@@ -79,7 +226,11 @@ impl Polygon<f64> {
         }
     }
 
-    pub fn clip(&self, bound: &[Point2<f64>; 2]) -> Option<Polygon<f64>> {
+    /// Clips this polygon to an axis-aligned rectangle (Sutherland–Hodgman).
+    ///
+    /// Edges lying on or parallel to the bound are kept or dropped whole, so this never needs to
+    /// intersect parallel lines. Returns `None` if nothing with a nonzero area remains.
+    pub fn clip<R: Into<Aabb2<f64>>>(&self, bound: R) -> Option<Polygon<f64>> {
         #[derive(Copy, Clone, Debug, PartialEq, Eq)]
         enum Side {
             Top,
@@ -87,119 +238,42 @@ impl Polygon<f64> {
             Left,
             Right,
         }
-        fn clip_side(
-            poly: Polygon<f64>,
-            bound: &[Point2<f64>; 2],
-            side: Side,
-        ) -> Option<Polygon<f64>> {
+        impl Side {
+            /// The coordinate axis this side is perpendicular to.
+            fn axis(self) -> usize {
+                match self {
+                    Side::Left | Side::Right => 0,
+                    Side::Top | Side::Bottom => 1,
+                }
+            }
+        }
+        fn clip_side(poly: Polygon<f64>, bound: &Aabb2<f64>, side: Side) -> Option<Polygon<f64>> {
+            let axis = side.axis();
+            let edge = match side {
+                Side::Left | Side::Bottom => bound.min[axis],
+                Side::Right | Side::Top => bound.max[axis],
+            };
+            let inside = |p: &Point2<f64>| match side {
+                Side::Left | Side::Bottom => p[axis] >= edge,
+                Side::Right | Side::Top => p[axis] <= edge,
+            };
+            // only called when a and b are on opposite sides, so a[axis] != b[axis]
+            let crossing = |a: &Point2<f64>, b: &Point2<f64>| {
+                let t = (edge - a[axis]) / (b[axis] - a[axis]);
+                let mut p = a + (b - a) * t;
+                p[axis] = edge;
+                p
+            };
             let mut res = Vec::new();
             let mut a = poly[poly.points.len() - 1];
             for b in poly.points {
-                match side {
-                    Side::Left => {
-                        if b.x >= bound[0].x {
-                            if a.x < bound[0].x {
-                                let intersect = inf_line_intersection(
-                                    &[a, b],
-                                    &[bound[0], Point2::new(bound[0].x, bound[1].y)],
-                                )
-                                .unwrap();
-                                match intersect {
-                                    Either::A(p) => res.push(p),
-                                    _ => panic!("Not Implemented: Parallel Clip"),
-                                }
-                            }
-                            res.push(b);
-                        } else if a.x >= bound[0].x {
-                            let intersect = inf_line_intersection(
-                                &[a, b],
-                                &[bound[0], Point2::new(bound[0].x, bound[1].y)],
-                            )
-                            .unwrap();
-                            match intersect {
-                                Either::A(p) => res.push(p),
-                                _ => panic!("Not Implemented: Parallel Clip"),
-                            }
-                        }
-                    }
-                    Side::Right => {
-                        if b.x <= bound[1].x {
-                            if a.x > bound[1].x {
-                                let intersect = inf_line_intersection(
-                                    &[a, b],
-                                    &[Point2::new(bound[1].x, bound[0].y), bound[1]],
-                                )
-                                .unwrap();
-                                match intersect {
-                                    Either::A(p) => res.push(p),
-                                    _ => panic!("Not Implemented: Parallel Clip"),
-                                }
-                            }
-                            res.push(b);
-                        } else if a.x <= bound[1].x {
-                            let intersect = inf_line_intersection(
-                                &[a, b],
-                                &[Point2::new(bound[1].x, bound[0].y), bound[1]],
-                            )
-                            .unwrap();
-                            match intersect {
-                                Either::A(p) => res.push(p),
-                                _ => panic!("Not Implemented: Parallel Clip"),
-                            }
-                        }
-                    }
-                    Side::Bottom => {
-                        if b.y >= bound[0].y {
-                            if a.y < bound[0].y {
-                                let intersect = inf_line_intersection(
-                                    &[a, b],
-                                    &[bound[0], Point2::new(bound[1].x, bound[0].y)],
-                                )
-                                .unwrap();
-                                match intersect {
-                                    Either::A(p) => res.push(p),
-                                    _ => panic!("Not Implemented: Parallel Clip"),
-                                }
-                            }
-                            res.push(b);
-                        } else if a.y >= bound[0].y {
-                            let intersect = inf_line_intersection(
-                                &[a, b],
-                                &[bound[0], Point2::new(bound[1].x, bound[0].y)],
-                            )
-                            .unwrap();
-                            match intersect {
-                                Either::A(p) => res.push(p),
-                                _ => panic!("Not Implemented: Parallel Clip"),
-                            }
-                        }
-                    }
-                    Side::Top => {
-                        if b.y <= bound[1].y {
-                            if a.y > bound[1].y {
-                                let intersect = inf_line_intersection(
-                                    &[a, b],
-                                    &[Point2::new(bound[0].x, bound[1].y), bound[1]],
-                                )
-                                .unwrap();
-                                match intersect {
-                                    Either::A(p) => res.push(p),
-                                    _ => panic!("Not Implemented: Parallel Clip"),
-                                }
-                            }
-                            res.push(b);
-                        } else if a.y <= bound[1].y {
-                            let intersect = inf_line_intersection(
-                                &[a, b],
-                                &[Point2::new(bound[0].x, bound[1].y), bound[1]],
-                            )
-                            .unwrap();
-                            match intersect {
-                                Either::A(p) => res.push(p),
-                                _ => panic!("Not Implemented: Parallel Clip"),
-                            }
-                        }
+                if inside(&b) {
+                    if !inside(&a) {
+                        res.push(crossing(&a, &b));
                     }
+                    res.push(b);
+                } else if inside(&a) {
+                    res.push(crossing(&a, &b));
                 }
                 a = b
             }
@@ -208,19 +282,83 @@ impl Polygon<f64> {
             }
             Some(Polygon { points: res })
         }
+        if self.points.is_empty() {
+            return None;
+        }
+        let bound = bound.into();
         clip_side(
             clip_side(
                 clip_side(
-                    clip_side(self.clone(), bound, Side::Left)?,
-                    bound,
+                    clip_side(self.clone(), &bound, Side::Left)?,
+                    &bound,
                     Side::Top,
                 )?,
-                bound,
+                &bound,
                 Side::Right,
             )?,
-            bound,
+            &bound,
             Side::Bottom,
-        )
+        )?
+        .non_degenerate()
+    }
+
+    /// Clips this polygon to the inside of a convex polygon of either winding (Sutherland–Hodgman).
+    ///
+    /// Returns `None` if nothing with a nonzero area remains, or if `clipper` has no area.
+    pub fn clip_convex(&self, clipper: &Polygon<f64>) -> Option<Polygon<f64>> {
+        let clipper = clipper.clone().non_degenerate()?;
+        let area = clipper.signed_area();
+        // positive for points to the left of a CCW clipper edge, or to the right of a CW one
+        let side =
+            |edge: &Line2<f64>, p: &Point2<f64>| orient(&edge[0], &edge[1], p) * area.signum();
+        let mut res = self.clone();
+        for edge in clipper.edges() {
+            if res.points.is_empty() {
+                return None;
+            }
+            let mut points = Vec::new();
+            let mut a = res[res.points.len() - 1];
+            let mut da = side(&edge, &a);
+            for b in res.points {
+                let db = side(&edge, &b);
+                if (da >= 0.0) != (db >= 0.0) {
+                    match inf_line_intersection(&[a, b], &edge) {
+                        Some(Either::A(p)) => points.push(p),
+                        // numerically parallel; fall back to interpolating by distance to the edge
+                        _ => points.push(a + (b - a) * (da / (da - db))),
+                    }
+                }
+                if db >= 0.0 {
+                    points.push(b);
+                }
+                a = b;
+                da = db;
+            }
+            res = Polygon { points };
+        }
+        res.non_degenerate()
+    }
+
+    /// Dedups this polygon, then discards it if it has fewer than three vertices or no area.
+    pub(crate) fn non_degenerate(mut self) -> Option<Polygon<f64>> {
+        self.dedup();
+        if self.points.len() < 3 {
+            return None;
+        }
+        if relative_eq!(self.signed_area(), 0.0) {
+            return None;
+        }
+        Some(self)
+    }
+
+    /// Removes consecutive duplicate vertices, including a last vertex that repeats the first.
+    pub fn dedup(&mut self) {
+        self.points.dedup_by(|a, b| relative_eq!(*a, *b));
+        while self.points.len() > 1
+            && relative_eq!(self.points[0], self.points[self.points.len() - 1])
+        {
+            self.points.pop();
+        }
     }
 
     pub fn min_y(&self) -> f64 {
@@ -258,19 +396,324 @@ impl Polygon<f64> {
     }
 }
 
-pub fn line_rect_intersection(
+pub fn line_rect_intersection<R: Into<Aabb2<f64>>>(
     line: &[Point2<f64>; 2],
-    rect: &[Point2<f64>; 2],
+    rect: R,
 ) -> Vec<Either<Point2<f64>, [Point2<f64>; 2]>> {
-    let tl = [rect[0].x, rect[1].y].into();
-    let br = [rect[1].x, rect[0].y].into();
-    let edges = [[rect[0], br], [rect[0], tl], [br, rect[1]], [tl, rect[1]]];
     let mut res = Vec::new();
-    for edge in edges.iter() {
-        let intersect = line_intersection(&edge, &line);
+    for edge in rect.into().edges().iter() {
+        let intersect = line_intersection(edge, line);
         if let Some(i) = intersect {
             res.push(i);
         }
     }
     res
 }
+
+#[cfg(test)]
+// the clip tests predate `Aabb2` and pass their corner pairs by reference, as older callers do
+#[allow(clippy::needless_borrows_for_generic_args)]
+mod test {
+    use super::*;
+
+    fn rect(a: [f64; 2], b: [f64; 2]) -> Line2<f64> {
+        [a.into(), b.into()]
+    }
+
+    fn assert_no_duplicates(poly: &Polygon<f64>) {
+        for edge in poly.edges() {
+            assert!(
+                !relative_eq!(edge[0], edge[1]),
+                "Duplicate vertex in {}",
+                poly
+            );
+        }
+    }
+
+    #[test]
+    fn clip_inside() {
+        let res = Polygon::square(1.0, 0.0, 0.0)
+            .clip(&rect([-2.0, -2.0], [2.0, 2.0]))
+            .unwrap();
+        assert_eq!(res.points.len(), 4);
+        assert_relative_eq!(res.signed_area(), 4.0);
+    }
+
+    #[test]
+    fn clip_overlap() {
+        let res = Polygon::square(1.0, 0.0, 0.0)
+            .clip(&rect([0.0, 0.0], [2.0, 2.0]))
+            .unwrap();
+        assert_no_duplicates(&res);
+        assert_eq!(res.points.len(), 4);
+        assert_relative_eq!(res.signed_area(), 1.0);
+    }
+
+    #[test]
+    fn clip_reversed_bound() {
+        let res = Polygon::square(1.0, 0.0, 0.0)
+            .clip(&rect([2.0, 2.0], [0.0, 0.0]))
+            .unwrap();
+        assert_relative_eq!(res.signed_area(), 1.0);
+    }
+
+    #[test]
+    fn clip_aabb() {
+        let bound = Aabb2::new(&Point2::new(2.0, 2.0), &Point2::new(0.0, 0.0));
+        let square = Polygon::square(1.0, 0.0, 0.0);
+        let res = square.clip(bound).unwrap();
+        assert_relative_eq!(res.signed_area(), 1.0);
+        assert_relative_eq!(square.clip(&bound).unwrap().signed_area(), 1.0);
+        assert_eq!(
+            square.aabb(),
+            Some(Aabb2::new(&Point2::new(-1.0, -1.0), &Point2::new(1.0, 1.0)))
+        );
+        let line = [Point2::new(-1.0, 1.0), Point2::new(3.0, 1.0)];
+        assert_eq!(line_rect_intersection(&line, bound).len(), 2);
+        assert_eq!(line_rect_intersection(&line, &bound).len(), 2);
+    }
+
+    #[test]
+    fn clip_outside() {
+        assert!(Polygon::square(1.0, 0.0, 0.0)
+            .clip(&rect([2.0, 2.0], [3.0, 3.0]))
+            .is_none());
+    }
+
+    #[test]
+    fn clip_edge_on_bound() {
+        // shares the bound's left edge
+        let res = Polygon::square(1.0, 0.0, 0.0)
+            .clip(&rect([-1.0, -1.0], [0.5, 0.5]))
+            .unwrap();
+        assert_no_duplicates(&res);
+        assert_relative_eq!(res.signed_area(), 2.25);
+        // only touches the bound along an edge
+        assert!(Polygon::square(1.0, 0.0, 0.0)
+            .clip(&rect([1.0, -1.0], [3.0, 1.0]))
+            .is_none());
+        // only touches the bound at a corner
+        assert!(Polygon::square(1.0, 0.0, 0.0)
+            .clip(&rect([1.0, 1.0], [3.0, 3.0]))
+            .is_none());
+    }
+
+    #[test]
+    fn clip_vertex_on_bound() {
+        let tri = Polygon {
+            points: vec![
+                Point2::new(0.0, 0.0),
+                Point2::new(2.0, 1.0),
+                Point2::new(0.0, 2.0),
+            ],
+        };
+        let res = tri.clip(&rect([0.0, 0.0], [2.0, 2.0])).unwrap();
+        assert_eq!(res.points.len(), 3);
+        assert_relative_eq!(res.signed_area(), 2.0);
+    }
+
+    #[test]
+    fn clip_zero_area() {
+        let line = Polygon {
+            points: vec![
+                Point2::new(-2.0, 0.0),
+                Point2::new(0.0, 0.0),
+                Point2::new(2.0, 0.0),
+            ],
+        };
+        assert!(line.clip(&rect([-1.0, -1.0], [1.0, 1.0])).is_none());
+        let point = Polygon {
+            points: vec![Point2::new(0.0, 0.0); 3],
+        };
+        assert!(point.clip(&rect([-1.0, -1.0], [1.0, 1.0])).is_none());
+        let empty = Polygon::<f64> { points: Vec::new() };
+        assert!(empty.clip(&rect([-1.0, -1.0], [1.0, 1.0])).is_none());
+    }
+
+    #[test]
+    fn clip_duplicate_vertices() {
+        let mut poly = Polygon::square(1.0, 0.0, 0.0);
+        poly.points.insert(1, poly[0]);
+        poly.points.push(poly[0]);
+        let res = poly.clip(&rect([0.0, -2.0], [2.0, 2.0])).unwrap();
+        assert_no_duplicates(&res);
+        assert_eq!(res.points.len(), 4);
+        assert_relative_eq!(res.signed_area(), 2.0);
+    }
+
+    #[test]
+    fn clip_degenerate_bound() {
+        assert!(Polygon::square(1.0, 0.0, 0.0)
+            .clip(&rect([0.0, -2.0], [0.0, 2.0]))
+            .is_none());
+    }
+
+    fn diamond(r: f64) -> Polygon<f64> {
+        Polygon {
+            points: vec![
+                Point2::new(r, 0.0),
+                Point2::new(0.0, r),
+                Point2::new(-r, 0.0),
+                Point2::new(0.0, -r),
+            ],
+        }
+    }
+
+    #[test]
+    fn clip_convex_rotated() {
+        let res = Polygon::square(1.0, 0.0, 0.0)
+            .clip_convex(&diamond(1.5))
+            .unwrap();
+        assert_no_duplicates(&res);
+        assert_eq!(res.points.len(), 8);
+        assert_relative_eq!(res.signed_area(), 3.5, epsilon = 1.0e-12);
+    }
+
+    #[test]
+    fn clip_convex_winding() {
+        let mut clipper = diamond(1.5);
+        clipper.points.reverse();
+        let res = Polygon::square(1.0, 0.0, 0.0)
+            .clip_convex(&clipper)
+            .unwrap();
+        assert_relative_eq!(res.signed_area(), 3.5, epsilon = 1.0e-12);
+    }
+
+    #[test]
+    fn clip_convex_matches_clip() {
+        let bound = rect([0.0, -0.5], [2.0, 2.0]);
+        let clipper = Polygon {
+            points: rect_to_vertices(&bound).to_vec(),
+        };
+        let poly = diamond(1.0);
+        assert_relative_eq!(
+            poly.clip_convex(&clipper).unwrap().signed_area(),
+            poly.clip(&bound).unwrap().signed_area(),
+            epsilon = 1.0e-12
+        );
+    }
+
+    #[test]
+    fn clip_convex_degenerate() {
+        assert!(Polygon::square(1.0, 0.0, 0.0)
+            .clip_convex(&Polygon::square(1.0, 3.0, 0.0))
+            .is_none());
+        assert!(Polygon::square(1.0, 0.0, 0.0)
+            .clip_convex(&Polygon::square(1.0, 2.0, 0.0))
+            .is_none());
+        let line = Polygon {
+            points: vec![Point2::new(-1.0, 0.0), Point2::new(1.0, 0.0)],
+        };
+        assert!(Polygon::square(1.0, 0.0, 0.0).clip_convex(&line).is_none());
+    }
+
+    #[test]
+    fn measurements() {
+        let square = Polygon::square(1.0, 2.0, -1.0);
+        assert_relative_eq!(square.signed_area(), 4.0);
+        assert_relative_eq!(square.area(), 4.0);
+        assert_relative_eq!(square.perimeter(), 8.0);
+        assert_relative_eq!(square.centroid(), Point2::new(2.0, -1.0));
+        let bounds = square.bounds().unwrap();
+        assert_relative_eq!(bounds[0], Point2::new(1.0, -2.0));
+        assert_relative_eq!(bounds[1], Point2::new(3.0, 0.0));
+        assert_eq!(square.winding(), Some(Winding::CounterClockwise));
+    }
+
+    #[test]
+    fn triangle_centroid() {
+        let tri = Polygon {
+            points: vec![
+                Point2::new(0.0, 0.0),
+                Point2::new(3.0, 0.0),
+                Point2::new(0.0, 3.0),
+            ],
+        };
+        assert_relative_eq!(tri.centroid(), Point2::new(1.0, 1.0));
+        assert_relative_eq!(tri.area(), 4.5);
+    }
+
+    #[test]
+    fn winding() {
+        let mut square = Polygon::square(1.0, 0.0, 0.0);
+        square.set_winding(Winding::Clockwise);
+        assert_eq!(square.winding(), Some(Winding::Clockwise));
+        assert_relative_eq!(square.signed_area(), -4.0);
+        assert_relative_eq!(square.area(), 4.0);
+        assert_relative_eq!(square.centroid(), Point2::new(0.0, 0.0));
+        square.set_winding(Winding::CounterClockwise);
+        assert_relative_eq!(square.signed_area(), 4.0);
+    }
+
+    #[test]
+    fn degenerate_measurements() {
+        let line = Polygon {
+            points: vec![Point2::new(0.0, 0.0), Point2::new(2.0, 0.0)],
+        };
+        assert_eq!(line.winding(), None);
+        assert_relative_eq!(line.centroid(), Point2::new(1.0, 0.0));
+        assert_relative_eq!(line.perimeter(), 4.0);
+        assert!(Polygon::<f64> { points: Vec::new() }.bounds().is_none());
+    }
+
+    #[test]
+    fn contains() {
+        let square = Polygon::square(1.0, 0.0, 0.0);
+        for &rule in &[FillRule::EvenOdd, FillRule::NonZero] {
+            assert!(square.contains(&Point2::new(0.5, -0.5), rule));
+            assert!(!square.contains(&Point2::new(1.5, 0.0), rule));
+            assert!(!square.contains(&Point2::new(0.0, -3.0), rule));
+        }
+        let mut cw = square.clone();
+        cw.set_winding(Winding::Clockwise);
+        assert!(cw.contains(&Point2::new(0.5, -0.5), FillRule::NonZero));
+        assert_eq!(cw.winding_number(&Point2::new(0.5, -0.5)), -1);
+    }
+
+    #[test]
+    fn fill_rules() {
+        // winds twice around the origin
+        let twice = Polygon {
+            points: (0..10)
+                .map(|i| {
+                    let angle = i as f64 * std::f64::consts::PI * 4.0 / 10.0;
+                    Point2::new(angle.cos(), angle.sin())
+                })
+                .collect(),
+        };
+        let origin = Point2::new(0.0, 0.0);
+        assert_eq!(twice.winding_number(&origin), 2);
+        assert!(twice.contains(&origin, FillRule::NonZero));
+        assert!(!twice.contains(&origin, FillRule::EvenOdd));
+    }
+
+    #[test]
+    fn locate() {
+        let square = Polygon::square(1.0, 0.0, 0.0);
+        let rule = FillRule::EvenOdd;
+        assert_eq!(
+            square.locate(&Point2::new(0.0, 0.0), rule),
+            Containment::Inside
+        );
+        assert_eq!(
+            square.locate(&Point2::new(2.0, 0.0), rule),
+            Containment::Outside
+        );
+        assert_eq!(
+            square.locate(&Point2::new(1.0, 0.3), rule),
+            Containment::Boundary
+        );
+        assert_eq!(
+            square.locate(&Point2::new(-1.0, -1.0), rule),
+            Containment::Boundary
+        );
+        assert_eq!(
+            square.locate(&Point2::new(0.2, 1.0), rule),
+            Containment::Boundary
+        );
+        assert_eq!(
+            square.locate(&Point2::new(1.0, 2.0), rule),
+            Containment::Outside
+        );
+    }
+}
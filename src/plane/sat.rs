@@ -0,0 +1,77 @@
+use super::polygon::*;
+use collision;
+use na::{Unit, Vector2};
+
+/// How two polygons overlap; see `collision::Penetration`.
+pub type Penetration = collision::Penetration<Vector2<f64>>;
+
+/// Tests two convex polygons for overlap by the separating axis theorem.
+///
+/// Returns `None` if some edge normal separates them, and otherwise the smallest push that would;
+/// polygons that only touch overlap with a depth of 0. Either winding works, but concave polygons
+/// are treated as their convex hulls would be only by chance.
+pub fn sat_collision(a: &Polygon<f64>, b: &Polygon<f64>) -> Option<Penetration> {
+    if a.points.is_empty() || b.points.is_empty() {
+        return None;
+    }
+    let axes = a
+        .edges()
+        .chain(b.edges())
+        .filter_map(|[p, q]| Unit::try_new(Vector2::new(q.y - p.y, p.x - q.x), 0.0));
+    collision::separating_axes(axes, &a.points, &b.points)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use na::Point2;
+
+    #[test]
+    fn squares() {
+        let a = Polygon::square(1.0, 0.0, 0.0);
+        let b = Polygon::square(1.0, 1.5, 0.25);
+        let pen = sat_collision(&a, &b).unwrap();
+        assert_relative_eq!(pen.depth, 0.5);
+        assert_relative_eq!(pen.axis.into_inner(), Vector2::x());
+        let pen = sat_collision(&b, &a).unwrap();
+        assert_relative_eq!(pen.axis.into_inner(), -Vector2::x());
+        assert!(sat_collision(&a, &Polygon::square(1.0, 2.5, 0.0)).is_none());
+        assert_relative_eq!(
+            sat_collision(&a, &Polygon::square(1.0, 2.0, 0.0))
+                .unwrap()
+                .depth,
+            0.0
+        );
+    }
+
+    #[test]
+    fn contained() {
+        // edge intersection alone misses this
+        let outer = Polygon::square(2.0, 0.0, 0.0);
+        let inner = Polygon::square(0.5, 0.0, 1.0);
+        let pen = sat_collision(&outer, &inner).unwrap();
+        assert_relative_eq!(pen.depth, 1.5);
+        assert_relative_eq!(pen.axis.into_inner(), Vector2::y());
+    }
+
+    #[test]
+    fn diagonal() {
+        let square = Polygon::square(1.0, 0.0, 0.0);
+        let mut tri = Polygon {
+            points: vec![
+                Point2::new(0.75, 1.5),
+                Point2::new(1.5, 0.75),
+                Point2::new(1.5, 1.5),
+            ],
+        };
+        // only the triangle's slanted edge separates them
+        assert!(sat_collision(&square, &tri).is_none());
+        for p in &mut tri.points {
+            *p -= Vector2::new(0.5, 0.5);
+        }
+        tri.points.reverse();
+        let pen = sat_collision(&square, &tri).unwrap();
+        assert_relative_eq!(pen.depth, 0.75 / 2.0f64.sqrt());
+        assert_relative_eq!(pen.axis.into_inner(), Vector2::new(1.0, 1.0).normalize());
+    }
+}
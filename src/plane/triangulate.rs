@@ -0,0 +1,349 @@
+use super::super::volume::polyhedron::Polyhedron;
+use super::multipolygon::*;
+use super::orient;
+use super::polygon::*;
+use na::Point2;
+
+impl Polygon<f64> {
+    /// Splits this polygon into triangles by ear clipping.
+    ///
+    /// Returns counter-clockwise index triples into `points`, in the face format used by
+    /// `Polyhedron`. Polygons without area produce no triangles. Vertices in the middle of a
+    /// straight edge still end up in a triangle, so that a mesh sharing the edge meets this one
+    /// without cracks; only repeated vertices are skipped.
+    ///
+    /// # Panics
+    /// If the polygon has more vertices than a `u16` can index.
+    pub fn triangulate(&self) -> Vec<[u16; 3]> {
+        PolygonWithHoles::from(self.clone()).triangulate()
+    }
+
+    /// Triangulates this polygon into a flat mesh sharing its vertices.
+    pub fn to_polyhedron(&self) -> Polyhedron<Point2<f64>> {
+        Polyhedron {
+            points: self.points.clone(),
+            faces: self.triangulate(),
+        }
+    }
+}
+
+impl PolygonWithHoles<f64> {
+    /// Splits this polygon into triangles by ear clipping, after bridging each hole into the outer
+    /// ring.
+    ///
+    /// Returns counter-clockwise index triples into the vertices of `rings()`, taken in order.
+    ///
+    /// # Panics
+    /// If the rings have more vertices in total than a `u16` can index.
+    pub fn triangulate(&self) -> Vec<[u16; 3]> {
+        let points = self
+            .rings()
+            .flat_map(|r| r.points.iter().cloned())
+            .collect::<Vec<_>>();
+        assert!(
+            points.len() <= u16::MAX as usize + 1,
+            "Too many vertices to triangulate: {}",
+            points.len()
+        );
+        let mut offset = 0;
+        let mut rings = Vec::new();
+        for (i, ring) in self.rings().enumerate() {
+            let mut ids = (offset..offset + ring.points.len()).collect::<Vec<_>>();
+            offset += ring.points.len();
+            let winding = match ring.winding() {
+                Some(w) => w,
+                // a hole without area doesn't take anything away
+                None if i > 0 => continue,
+                None => return Vec::new(),
+            };
+            // the outer ring must be counter-clockwise and holes clockwise
+            if (winding == Winding::CounterClockwise) != (i == 0) {
+                ids.reverse();
+            }
+            rings.push(ids);
+        }
+        let mut holes = rings.split_off(1);
+        let mut outer = rings.pop().unwrap();
+        // bridge the rightmost holes first, so that later bridges can't be blocked by earlier ones
+        holes.sort_by(|l, r| {
+            let max = |ring: &Vec<usize>| {
+                ring.iter()
+                    .map(|&i| points[i].x)
+                    .fold(f64::NEG_INFINITY, f64::max)
+            };
+            max(r).partial_cmp(&max(l)).unwrap()
+        });
+        while !holes.is_empty() {
+            let hole = holes.remove(0);
+            outer = bridge(&points, &outer, &hole, &holes);
+        }
+        clip_ears(&points, outer)
+    }
+
+    /// Triangulates this polygon into a flat mesh sharing the vertices of its rings.
+    pub fn to_polyhedron(&self) -> Polyhedron<Point2<f64>> {
+        Polyhedron {
+            points: self
+                .rings()
+                .flat_map(|r| r.points.iter().cloned())
+                .collect(),
+            faces: self.triangulate(),
+        }
+    }
+}
+
+/// Whether two segments cross at a point interior to both.
+fn crosses(a: &[Point2<f64>; 2], b: &[Point2<f64>; 2]) -> bool {
+    let side = |l: &[Point2<f64>; 2], p: &Point2<f64>| orient(&l[0], &l[1], p);
+    let (a0, a1) = (side(b, &a[0]), side(b, &a[1]));
+    let (b0, b1) = (side(a, &b[0]), side(a, &b[1]));
+    ((a0 > 0.0 && a1 < 0.0) || (a0 < 0.0 && a1 > 0.0))
+        && ((b0 > 0.0 && b1 < 0.0) || (b0 < 0.0 && b1 > 0.0))
+}
+
+/// Whether `p` lies on the open segment `l`.
+fn on_segment(l: &[Point2<f64>; 2], p: &Point2<f64>) -> bool {
+    let d = l[1] - l[0];
+    let t = (p - l[0]).dot(&d) / d.dot(&d);
+    t > 0.0 && t < 1.0 && orient(&l[0], &l[1], p) == 0.0
+}
+
+/// Joins a clockwise hole into a counter-clockwise ring through a pair of mutually visible
+/// vertices, producing a single ring that doubles back along the bridge.
+fn bridge(
+    points: &[Point2<f64>],
+    outer: &[usize],
+    hole: &[usize],
+    others: &[Vec<usize>],
+) -> Vec<usize> {
+    let (mi, &m) = hole
+        .iter()
+        .enumerate()
+        .max_by(|l, r| points[*l.1].x.partial_cmp(&points[*r.1].x).unwrap())
+        .unwrap();
+    let mp = points[m];
+    let edges = |ring: &[usize]| {
+        (0..ring.len())
+            .map(|i| (ring[i], ring[(i + 1) % ring.len()]))
+            .collect::<Vec<_>>()
+    };
+    let blockers = std::iter::once(outer)
+        .chain(std::iter::once(hole))
+        .chain(others.iter().map(|r| r.as_slice()))
+        .flat_map(edges)
+        .collect::<Vec<_>>();
+    let visible = |v: usize| {
+        let line = [mp, points[v]];
+        !blockers.iter().any(|&(a, b)| {
+            a != v && b != v && a != m && b != m && crosses(&line, &[points[a], points[b]])
+        }) && !points.iter().any(|p| on_segment(&line, p))
+    };
+    // the rightmost hole vertex always sees some ring vertex at least as far right
+    let mut candidates = (0..outer.len())
+        .filter(|&i| points[outer[i]].x >= mp.x)
+        .collect::<Vec<_>>();
+    candidates.sort_by(|&l, &r| {
+        let dist = |i: usize| na::distance_squared(&points[outer[i]], &mp);
+        dist(l).partial_cmp(&dist(r)).unwrap()
+    });
+    let vi = candidates
+        .into_iter()
+        .find(|&i| visible(outer[i]))
+        .unwrap_or_else(|| {
+            // numerically stuck; fall back to the nearest vertex
+            (0..outer.len())
+                .min_by(|&l, &r| {
+                    let dist = |i: usize| na::distance_squared(&points[outer[i]], &mp);
+                    dist(l).partial_cmp(&dist(r)).unwrap()
+                })
+                .unwrap()
+        });
+    let mut res = Vec::with_capacity(outer.len() + hole.len() + 2);
+    res.extend_from_slice(&outer[..=vi]);
+    res.extend_from_slice(&hole[mi..]);
+    res.extend_from_slice(&hole[..=mi]);
+    res.extend_from_slice(&outer[vi..]);
+    res
+}
+
+/// Ear clipping over a counter-clockwise ring of vertex indices.
+fn clip_ears(points: &[Point2<f64>], mut ring: Vec<usize>) -> Vec<[u16; 3]> {
+    let mut res = Vec::new();
+    while ring.len() >= 3 {
+        let len = ring.len();
+        let corner = |i: usize| {
+            let (p, c, n) = (ring[(i + len - 1) % len], ring[i], ring[(i + 1) % len]);
+            let area = orient(&points[p], &points[c], &points[n]);
+            (p, c, n, area)
+        };
+        let is_ear = |i: usize| {
+            let (p, c, n, area) = corner(i);
+            if area <= 0.0 {
+                return false;
+            }
+            let tri = [points[p], points[c], points[n]];
+            !ring.iter().any(|&o| {
+                let q = points[o];
+                if tri.iter().any(|t| relative_eq!(*t, q)) {
+                    return false;
+                }
+                // vertices on the new diagonal would leave it touching the rest of the ring
+                on_segment(&[tri[2], tri[0]], &q)
+                    || (0..3).all(|j| orient(&tri[j], &tri[(j + 1) % 3], &q) > 0.0)
+            })
+        };
+        // drop repeated vertices without emitting a triangle; vertices in the middle of a straight
+        // edge stay, so that meshes sharing the edge don't end up with cracks along it
+        if let Some(i) = (0..len).find(|&i| {
+            let (p, c, _, _) = corner(i);
+            relative_eq!(points[p], points[c])
+        }) {
+            ring.remove(i);
+            continue;
+        }
+        let ear = (0..len).find(|&i| is_ear(i)).unwrap_or_else(|| {
+            // numerically stuck; take the most convex corner so that clipping still terminates
+            (0..len)
+                .max_by(|&l, &r| corner(l).3.partial_cmp(&corner(r).3).unwrap())
+                .unwrap()
+        });
+        let (p, c, n, area) = corner(ear);
+        if area > 0.0 {
+            res.push([p as u16, c as u16, n as u16]);
+        }
+        ring.remove(ear);
+    }
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn area(points: &[Point2<f64>], tris: &[[u16; 3]]) -> f64 {
+        tris.iter()
+            .map(|t| {
+                let (a, b, c) = (
+                    points[t[0] as usize],
+                    points[t[1] as usize],
+                    points[t[2] as usize],
+                );
+                let area = (b - a).perp(&(c - a)) / 2.0;
+                assert!(area > 0.0, "Triangle {:?} is not counter-clockwise", t);
+                area
+            })
+            .sum()
+    }
+
+    #[test]
+    fn square() {
+        let square = Polygon::square(1.0, 0.0, 0.0);
+        let tris = square.triangulate();
+        assert_eq!(tris.len(), 2);
+        assert_relative_eq!(area(&square.points, &tris), 4.0);
+    }
+
+    #[test]
+    fn clockwise() {
+        let mut square = Polygon::square(1.0, 0.0, 0.0);
+        square.points.reverse();
+        assert_relative_eq!(area(&square.points, &square.triangulate()), 4.0);
+    }
+
+    #[test]
+    fn concave() {
+        let l = Polygon {
+            points: vec![
+                Point2::new(0.0, 0.0),
+                Point2::new(2.0, 0.0),
+                Point2::new(2.0, 1.0),
+                Point2::new(1.0, 1.0),
+                Point2::new(1.0, 2.0),
+                Point2::new(0.0, 2.0),
+            ],
+        };
+        let tris = l.triangulate();
+        assert_eq!(tris.len(), 4);
+        assert_relative_eq!(area(&l.points, &tris), 3.0);
+    }
+
+    #[test]
+    fn collinear_vertices() {
+        let mut square = Polygon::square(1.0, 0.0, 0.0);
+        square.points.insert(1, Point2::new(0.0, -1.0));
+        let tris = square.triangulate();
+        assert_relative_eq!(area(&square.points, &tris), 4.0);
+    }
+
+    #[test]
+    fn collinear_vertices_kept() {
+        // two extra vertices along the bottom edge and one along the right
+        let ring = Polygon {
+            points: vec![
+                Point2::new(0.0, 0.0),
+                Point2::new(1.0, 0.0),
+                Point2::new(2.0, 0.0),
+                Point2::new(3.0, 0.0),
+                Point2::new(3.0, 1.0),
+                Point2::new(3.0, 2.0),
+                Point2::new(0.0, 2.0),
+            ],
+        };
+        let tris = ring.triangulate();
+        assert_eq!(tris.len(), ring.points.len() - 2);
+        assert_relative_eq!(area(&ring.points, &tris), 6.0);
+        for i in 0..ring.points.len() as u16 {
+            assert!(
+                tris.iter().any(|t| t.contains(&i)),
+                "Vertex {} was dropped",
+                i
+            );
+        }
+    }
+
+    #[test]
+    fn holes() {
+        let poly = PolygonWithHoles {
+            outer: Polygon::square(4.0, 0.0, 0.0),
+            holes: vec![
+                Polygon::square(1.0, -2.0, 0.0),
+                Polygon::square(1.0, 2.0, 0.0),
+                Polygon::square(1.0, 0.0, 2.5),
+            ],
+        };
+        let mesh = poly.to_polyhedron();
+        assert_eq!(mesh.points.len(), 16);
+        assert_relative_eq!(area(&mesh.points, &mesh.faces), poly.area());
+    }
+
+    #[test]
+    fn degenerate() {
+        let line = Polygon {
+            points: vec![
+                Point2::new(0.0, 0.0),
+                Point2::new(1.0, 0.0),
+                Point2::new(2.0, 0.0),
+            ],
+        };
+        assert!(line.triangulate().is_empty());
+        assert!(Polygon::<f64> { points: Vec::new() }
+            .triangulate()
+            .is_empty());
+    }
+
+    #[test]
+    fn star() {
+        let star = Polygon {
+            points: (0..16)
+                .map(|i| {
+                    let angle = i as f64 * std::f64::consts::PI / 8.0;
+                    let r = if i % 2 == 0 { 1.0 } else { 0.3 };
+                    Point2::new(angle.cos() * r, angle.sin() * r)
+                })
+                .collect(),
+        };
+        let tris = star.triangulate();
+        assert_eq!(tris.len(), 14);
+        assert_relative_eq!(area(&star.points, &tris), star.area(), epsilon = 1.0e-12);
+    }
+}
@@ -0,0 +1,246 @@
+//! Adaptive-precision geometric predicates, after Shewchuk's "Adaptive Precision Floating-Point
+//! Arithmetic and Fast Robust Geometric Predicates".
+//!
+//! Each predicate first evaluates its determinant in ordinary floating point, and only when the
+//! result is too close to zero for its sign to be trusted does it recompute the determinant
+//! exactly, using floating-point expansions. The sign of every result is always correct; the
+//! magnitude is only an approximation.
+
+use na::{Point2, Point3};
+
+/// Half an ulp of 1.0; the relative error of a single rounded operation.
+const EPSILON: f64 = f64::EPSILON / 2.0;
+const CCW_BOUND: f64 = (3.0 + 16.0 * EPSILON) * EPSILON;
+const O3D_BOUND: f64 = (7.0 + 56.0 * EPSILON) * EPSILON;
+const ICC_BOUND: f64 = (10.0 + 96.0 * EPSILON) * EPSILON;
+
+/// Positive if `a`, `b` and `c` wind counter-clockwise, negative if clockwise, and zero if they are
+/// collinear.
+pub fn orient2d(a: &Point2<f64>, b: &Point2<f64>, c: &Point2<f64>) -> f64 {
+    let left = (a.x - c.x) * (b.y - c.y);
+    let right = (a.y - c.y) * (b.x - c.x);
+    let det = left - right;
+    if det.abs() >= CCW_BOUND * (left.abs() + right.abs()) {
+        return det;
+    }
+    let left = product(&diff(a.x, c.x), &diff(b.y, c.y));
+    let right = product(&diff(a.y, c.y), &diff(b.x, c.x));
+    estimate(&sum(&left, &negate(&right)))
+}
+
+/// Positive if `d` lies below the plane through `a`, `b` and `c`, where "below" is the side from
+/// which `a`, `b` and `c` appear clockwise. Negative if above, and zero if the points are coplanar.
+pub fn orient3d(a: &Point3<f64>, b: &Point3<f64>, c: &Point3<f64>, d: &Point3<f64>) -> f64 {
+    let (ad, bd, cd) = (a - d, b - d, c - d);
+    let (bdxcdy, cdxbdy) = (bd.x * cd.y, cd.x * bd.y);
+    let (cdxady, adxcdy) = (cd.x * ad.y, ad.x * cd.y);
+    let (adxbdy, bdxady) = (ad.x * bd.y, bd.x * ad.y);
+    let det = ad.z * (bdxcdy - cdxbdy) + bd.z * (cdxady - adxcdy) + cd.z * (adxbdy - bdxady);
+    let permanent = (bdxcdy.abs() + cdxbdy.abs()) * ad.z.abs()
+        + (cdxady.abs() + adxcdy.abs()) * bd.z.abs()
+        + (adxbdy.abs() + bdxady.abs()) * cd.z.abs();
+    if det.abs() >= O3D_BOUND * permanent {
+        return det;
+    }
+    let ad = [diff(a.x, d.x), diff(a.y, d.y), diff(a.z, d.z)];
+    let bd = [diff(b.x, d.x), diff(b.y, d.y), diff(b.z, d.z)];
+    let cd = [diff(c.x, d.x), diff(c.y, d.y), diff(c.z, d.z)];
+    let minor = |p: &[Vec<f64>; 3], q: &[Vec<f64>; 3]| {
+        sum(&product(&p[0], &q[1]), &negate(&product(&q[0], &p[1])))
+    };
+    let res = sum(
+        &sum(
+            &product(&ad[2], &minor(&bd, &cd)),
+            &product(&bd[2], &minor(&cd, &ad)),
+        ),
+        &product(&cd[2], &minor(&ad, &bd)),
+    );
+    estimate(&res)
+}
+
+/// Positive if `d` lies inside the circle through `a`, `b` and `c`, negative if outside, and zero
+/// if the four points are cocircular. `a`, `b` and `c` must wind counter-clockwise, or the sign is
+/// reversed.
+pub fn incircle(a: &Point2<f64>, b: &Point2<f64>, c: &Point2<f64>, d: &Point2<f64>) -> f64 {
+    let (ad, bd, cd) = (a - d, b - d, c - d);
+    let (bdxcdy, cdxbdy) = (bd.x * cd.y, cd.x * bd.y);
+    let (cdxady, adxcdy) = (cd.x * ad.y, ad.x * cd.y);
+    let (adxbdy, bdxady) = (ad.x * bd.y, bd.x * ad.y);
+    let (alift, blift, clift) = (ad.norm_squared(), bd.norm_squared(), cd.norm_squared());
+    let det = alift * (bdxcdy - cdxbdy) + blift * (cdxady - adxcdy) + clift * (adxbdy - bdxady);
+    let permanent = (bdxcdy.abs() + cdxbdy.abs()) * alift
+        + (cdxady.abs() + adxcdy.abs()) * blift
+        + (adxbdy.abs() + bdxady.abs()) * clift;
+    if det.abs() >= ICC_BOUND * permanent {
+        return det;
+    }
+    let ad = [diff(a.x, d.x), diff(a.y, d.y)];
+    let bd = [diff(b.x, d.x), diff(b.y, d.y)];
+    let cd = [diff(c.x, d.x), diff(c.y, d.y)];
+    let lift = |p: &[Vec<f64>; 2]| sum(&product(&p[0], &p[0]), &product(&p[1], &p[1]));
+    let minor = |p: &[Vec<f64>; 2], q: &[Vec<f64>; 2]| {
+        sum(&product(&p[0], &q[1]), &negate(&product(&q[0], &p[1])))
+    };
+    let res = sum(
+        &sum(
+            &product(&lift(&ad), &minor(&bd, &cd)),
+            &product(&lift(&bd), &minor(&cd, &ad)),
+        ),
+        &product(&lift(&cd), &minor(&ad, &bd)),
+    );
+    estimate(&res)
+}
+
+// Expansions are lists of nonoverlapping floats in order of increasing magnitude, whose exact sum
+// is the value they represent. Zero components are never stored.
+
+/// `a + b` as `(sum, roundoff)`.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let bv = x - a;
+    let av = x - bv;
+    (x, (a - av) + (b - bv))
+}
+
+/// `a - b` as an expansion.
+fn diff(a: f64, b: f64) -> Vec<f64> {
+    let x = a - b;
+    let bv = a - x;
+    let av = x + bv;
+    let y = (a - av) + (bv - b);
+    [y, x].iter().cloned().filter(|c| *c != 0.0).collect()
+}
+
+/// Adds a single float to an expansion.
+fn grow(e: &[f64], b: f64) -> Vec<f64> {
+    let mut res = Vec::with_capacity(e.len() + 1);
+    let mut q = b;
+    for &c in e {
+        let (s, h) = two_sum(q, c);
+        q = s;
+        if h != 0.0 {
+            res.push(h);
+        }
+    }
+    if q != 0.0 {
+        res.push(q);
+    }
+    res
+}
+
+fn sum(e: &[f64], f: &[f64]) -> Vec<f64> {
+    f.iter().fold(e.to_vec(), |acc, &c| grow(&acc, c))
+}
+
+fn negate(e: &[f64]) -> Vec<f64> {
+    e.iter().map(|c| -c).collect()
+}
+
+/// Multiplies an expansion by a single float.
+fn scale(e: &[f64], b: f64) -> Vec<f64> {
+    let mut res = Vec::new();
+    for &c in e {
+        let p = c * b;
+        // fused multiply-add recovers the product's roundoff exactly
+        let err = c.mul_add(b, -p);
+        if err != 0.0 {
+            res = grow(&res, err);
+        }
+        if p != 0.0 {
+            res = grow(&res, p);
+        }
+    }
+    res
+}
+
+fn product(e: &[f64], f: &[f64]) -> Vec<f64> {
+    f.iter().fold(Vec::new(), |acc, &c| sum(&acc, &scale(e, c)))
+}
+
+/// The largest component of an expansion, which has the sign of the whole.
+fn estimate(e: &[f64]) -> f64 {
+    e.last().cloned().unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn orient2d_signs() {
+        let (a, b) = (Point2::new(0.0, 0.0), Point2::new(1.0, 0.0));
+        assert!(orient2d(&a, &b, &Point2::new(0.5, 1.0)) > 0.0);
+        assert!(orient2d(&a, &b, &Point2::new(0.5, -1.0)) < 0.0);
+        assert_eq!(orient2d(&a, &b, &Point2::new(3.0, 0.0)), 0.0);
+    }
+
+    #[test]
+    fn orient2d_near_degenerate() {
+        // points a hair off the line y = x, where the naive cross product often rounds to zero
+        let a = Point2::new(0.5, 0.5);
+        let b = Point2::new(12.0, 12.0);
+        let c = Point2::new(24.0, 24.0);
+        assert_eq!(orient2d(&a, &b, &c), 0.0);
+        let step = f64::EPSILON;
+        let mut misjudged = 0;
+        for i in 0..32 {
+            let p = Point2::new(0.5 + step * i as f64, 0.5);
+            let naive = (b.x - p.x) * (c.y - p.y) - (b.y - p.y) * (c.x - p.x);
+            let exact = orient2d(&p, &b, &c);
+            if i == 0 {
+                assert_eq!(exact, 0.0);
+            } else {
+                assert!(exact < 0.0, "{}: naive {} exact {}", i, naive, exact);
+                if naive >= 0.0 {
+                    misjudged += 1;
+                }
+            }
+        }
+        assert!(misjudged > 0);
+    }
+
+    #[test]
+    fn orient3d_signs() {
+        let a = Point3::new(0.0, 0.0, 0.0);
+        let b = Point3::new(1.0, 0.0, 0.0);
+        let c = Point3::new(0.0, 1.0, 0.0);
+        assert!(orient3d(&a, &b, &c, &Point3::new(0.0, 0.0, -1.0)) > 0.0);
+        assert!(orient3d(&a, &b, &c, &Point3::new(0.0, 0.0, 1.0)) < 0.0);
+        assert_eq!(orient3d(&a, &b, &c, &Point3::new(5.0, -3.0, 0.0)), 0.0);
+        let tiny = Point3::new(0.3, 0.3, 1.0e-300);
+        assert!(orient3d(&a, &b, &c, &tiny) < 0.0);
+    }
+
+    #[test]
+    fn orient3d_near_degenerate() {
+        let a = Point3::new(0.1, 0.2, 0.3);
+        let b = Point3::new(1.1, 0.7, 0.9);
+        let c = Point3::new(0.4, 1.3, 0.2);
+        // one ulp above `a`, which is still off the plane
+        let d = Point3::new(0.1, 0.2, 0.3 + f64::EPSILON / 4.0);
+        assert!(orient3d(&a, &b, &c, &d) != 0.0);
+        assert_eq!(orient3d(&a, &b, &c, &a), 0.0);
+        // within rounding of the plane, where the plain determinant gets the sign wrong
+        let naive = |d: &Point3<f64>| {
+            let (ad, bd, cd) = (a - d, b - d, c - d);
+            ad.z * (bd.x * cd.y - cd.x * bd.y)
+                + bd.z * (cd.x * ad.y - ad.x * cd.y)
+                + cd.z * (ad.x * bd.y - bd.x * ad.y)
+        };
+        let near = Point3::new(0.2675, 0.735, 0.2675);
+        assert!(naive(&near) > 0.0);
+        assert!(orient3d(&a, &b, &c, &near) < 0.0);
+    }
+
+    #[test]
+    fn incircle_signs() {
+        let a = Point2::new(1.0, 0.0);
+        let b = Point2::new(0.0, 1.0);
+        let c = Point2::new(-1.0, 0.0);
+        assert!(incircle(&a, &b, &c, &Point2::new(0.0, 0.0)) > 0.0);
+        assert!(incircle(&a, &b, &c, &Point2::new(2.0, 0.0)) < 0.0);
+        assert_eq!(incircle(&a, &b, &c, &Point2::new(0.0, -1.0)), 0.0);
+        let just_outside = Point2::new(0.0, -1.0 - f64::EPSILON);
+        assert!(incircle(&a, &b, &c, &just_outside) < 0.0);
+    }
+}
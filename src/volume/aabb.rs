@@ -0,0 +1,230 @@
+use super::*;
+use na::{Point3, RealField, Scalar, Vector3};
+
+/// An axis-aligned box, stored as its minimum and maximum corners; the 3D counterpart of
+/// `plane::aabb::Aabb2`.
+///
+/// Anything that takes a box also takes the two opposite corners as a `Line3`, in either order,
+/// through `From`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb3<N: Scalar> {
+    pub min: Point3<N>,
+    pub max: Point3<N>,
+}
+
+impl<N: RealField> Aabb3<N> {
+    /// The box with `a` and `b` as opposite corners.
+    pub fn new(a: &Point3<N>, b: &Point3<N>) -> Self {
+        Aabb3 {
+            min: Point3::from(a.coords.zip_map(&b.coords, |a, b| a.min(b))),
+            max: Point3::from(a.coords.zip_map(&b.coords, |a, b| a.max(b))),
+        }
+    }
+
+    /// A box inside out, so that it holds nothing and any point or box added to it becomes the
+    /// whole result.
+    pub fn empty() -> Self {
+        Aabb3 {
+            min: Point3::new(N::max_value(), N::max_value(), N::max_value()),
+            max: Point3::new(N::min_value(), N::min_value(), N::min_value()),
+        }
+    }
+
+    /// The smallest box holding every point, or `None` if there are none.
+    pub fn from_points<'a, I: IntoIterator<Item = &'a Point3<N>>>(points: I) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = *points.next()?;
+        Some(points.fold(
+            Aabb3 {
+                min: first,
+                max: first,
+            },
+            |b, p| b.include(p),
+        ))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..3).any(|i| self.min[i] > self.max[i])
+    }
+
+    pub fn center(&self) -> Point3<N> {
+        na::center(&self.min, &self.max)
+    }
+
+    pub fn size(&self) -> Vector3<N> {
+        self.max - self.min
+    }
+
+    pub fn volume(&self) -> N {
+        if self.is_empty() {
+            N::zero()
+        } else {
+            let size = self.size();
+            size.x * size.y * size.z
+        }
+    }
+
+    /// The corners, with bit 0 of the index choosing `max.x` over `min.x`, bit 1 `max.y` and bit
+    /// 2 `max.z`.
+    pub fn vertices(&self) -> [Point3<N>; 8] {
+        let corner = |i: usize| {
+            Point3::new(
+                if i & 1 == 0 { self.min.x } else { self.max.x },
+                if i & 2 == 0 { self.min.y } else { self.max.y },
+                if i & 4 == 0 { self.min.z } else { self.max.z },
+            )
+        };
+        [
+            corner(0),
+            corner(1),
+            corner(2),
+            corner(3),
+            corner(4),
+            corner(5),
+            corner(6),
+            corner(7),
+        ]
+    }
+
+    /// Whether the point is inside or on the boundary.
+    pub fn contains_point(&self, p: &Point3<N>) -> bool {
+        (0..3).all(|i| self.min[i] <= p[i] && p[i] <= self.max[i])
+    }
+
+    /// Whether `other` lies entirely inside this box, boundary included.
+    pub fn contains(&self, other: &Aabb3<N>) -> bool {
+        (0..3).all(|i| self.min[i] <= other.min[i] && other.max[i] <= self.max[i])
+    }
+
+    /// Whether the boxes share any point, including when one contains the other or they only
+    /// touch.
+    pub fn overlaps(&self, other: &Aabb3<N>) -> bool {
+        (0..3).all(|i| self.min[i] <= other.max[i] && other.min[i] <= self.max[i])
+    }
+
+    /// The smallest box holding both.
+    pub fn union(&self, other: &Aabb3<N>) -> Self {
+        Aabb3 {
+            min: Point3::from(self.min.coords.zip_map(&other.min.coords, |a, b| a.min(b))),
+            max: Point3::from(self.max.coords.zip_map(&other.max.coords, |a, b| a.max(b))),
+        }
+    }
+
+    /// The space both boxes cover, or `None` if they don't overlap. Boxes that only touch give one
+    /// that is flat along some axis.
+    pub fn intersection(&self, other: &Aabb3<N>) -> Option<Self> {
+        if !self.overlaps(other) {
+            return None;
+        }
+        Some(Aabb3 {
+            min: Point3::from(self.min.coords.zip_map(&other.min.coords, |a, b| a.max(b))),
+            max: Point3::from(self.max.coords.zip_map(&other.max.coords, |a, b| a.min(b))),
+        })
+    }
+
+    /// The smallest box holding this one and `p`.
+    pub fn include(&self, p: &Point3<N>) -> Self {
+        Aabb3 {
+            min: Point3::from(self.min.coords.zip_map(&p.coords, |a, b| a.min(b))),
+            max: Point3::from(self.max.coords.zip_map(&p.coords, |a, b| a.max(b))),
+        }
+    }
+
+    /// Grows every face outwards by `margin`, or shrinks them if it's negative.
+    pub fn expand(&self, margin: N) -> Self {
+        let margin = Vector3::new(margin, margin, margin);
+        Aabb3 {
+            min: self.min - margin,
+            max: self.max + margin,
+        }
+    }
+
+    /// The point of the box nearest `p`, which is `p` itself when it's inside.
+    pub fn closest_point(&self, p: &Point3<N>) -> Point3<N> {
+        Point3::from(
+            p.coords
+                .zip_map(&self.min.coords, |a, b| a.max(b))
+                .zip_map(&self.max.coords, |a, b| a.min(b)),
+        )
+    }
+
+    pub fn distance_squared(&self, p: &Point3<N>) -> N {
+        na::distance_squared(p, &self.closest_point(p))
+    }
+
+    pub fn distance(&self, p: &Point3<N>) -> N {
+        na::distance(p, &self.closest_point(p))
+    }
+}
+
+impl<N: RealField> From<Line3<N>> for Aabb3<N> {
+    fn from(corners: Line3<N>) -> Self {
+        Aabb3::new(&corners[0], &corners[1])
+    }
+}
+
+impl<'a, N: RealField> From<&'a Line3<N>> for Aabb3<N> {
+    fn from(corners: &'a Line3<N>) -> Self {
+        Aabb3::new(&corners[0], &corners[1])
+    }
+}
+
+impl<'a, N: RealField> From<&'a Aabb3<N>> for Aabb3<N> {
+    fn from(aabb: &'a Aabb3<N>) -> Self {
+        *aabb
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn aabb(a: [f64; 3], b: [f64; 3]) -> Aabb3<f64> {
+        Aabb3::new(&a.into(), &b.into())
+    }
+
+    #[test]
+    fn nested() {
+        let outer = aabb([2.0, 2.0, 2.0], [-2.0, -2.0, -2.0]);
+        let inner = aabb([-0.5, 0.0, 0.0], [0.5, 1.0, 1.5]);
+        assert_eq!(outer.min, Point3::new(-2.0, -2.0, -2.0));
+        assert!(outer.overlaps(&inner) && inner.overlaps(&outer));
+        assert!(outer.contains(&inner) && !inner.contains(&outer));
+        assert_eq!(outer.intersection(&inner), Some(inner));
+        assert_eq!(outer.union(&inner), outer);
+        assert!(inner.vertices().iter().all(|p| outer.contains_point(p)));
+    }
+
+    #[test]
+    fn disjoint() {
+        let a = aabb([0.0, 0.0, 0.0], [1.0, 1.0, 1.0]);
+        let b = aabb([0.5, 0.5, 2.0], [3.0, 3.0, 3.0]);
+        assert!(!a.overlaps(&b));
+        assert!(a.intersection(&b).is_none());
+        assert_eq!(a.union(&b), aabb([0.0, 0.0, 0.0], [3.0, 3.0, 3.0]));
+        let touching = aabb([0.5, 0.5, 1.0], [2.0, 2.0, 2.0]);
+        assert_relative_eq!(a.intersection(&touching).unwrap().volume(), 0.0);
+        assert!(a.expand(1.0).overlaps(&b));
+        assert!(!a.expand(-0.25).overlaps(&touching));
+    }
+
+    #[test]
+    fn points() {
+        let pts = [
+            Point3::new(1.0, 3.0, 0.0),
+            Point3::new(-1.0, 0.5, 2.0),
+            Point3::new(2.0, 1.0, -1.0),
+        ];
+        let b = Aabb3::from_points(&pts).unwrap();
+        assert_eq!(b, aabb([-1.0, 0.5, -1.0], [2.0, 3.0, 2.0]));
+        assert!(Aabb3::<f64>::from_points(&[]).is_none());
+        assert!(Aabb3::<f64>::empty().is_empty());
+        assert_relative_eq!(b.volume(), 22.5);
+        assert_relative_eq!(
+            b.closest_point(&Point3::new(0.0, 5.0, 0.0)),
+            Point3::new(0.0, 3.0, 0.0)
+        );
+        assert_relative_eq!(b.distance(&Point3::new(5.0, 7.0, 0.0)), 5.0);
+        assert_relative_eq!(b.distance(&Point3::new(0.0, 1.0, 0.0)), 0.0);
+    }
+}
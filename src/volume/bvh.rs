@@ -0,0 +1,517 @@
+use super::polyhedron::*;
+use super::ray::*;
+use super::*;
+use na::Point3;
+
+/// Faces a node may hold before the builder considers splitting it.
+const MIN_SPLIT: usize = 2;
+/// Leaves are split even when it doesn't look cheaper, once they grow past this many faces.
+const MAX_LEAF: usize = 8;
+/// Buckets per axis when estimating split costs.
+const BINS: usize = 12;
+
+impl Aabb3<f64> {
+    /// Half the surface area, which is all the split heuristic needs.
+    fn half_area(&self) -> f64 {
+        let d = self.size();
+        if self.is_empty() {
+            0.0
+        } else {
+            d.x * d.y + d.y * d.z + d.z * d.x
+        }
+    }
+
+    /// How far along the ray it enters this box, if it does so before `limit`.
+    fn entry(&self, ray: &Ray3, limit: f64) -> Option<f64> {
+        let (mut near, mut far) = (0.0f64, limit);
+        for i in 0..3 {
+            let inv = 1.0 / ray.dir[i];
+            let t0 = (self.min[i] - ray.origin[i]) * inv;
+            let t1 = (self.max[i] - ray.origin[i]) * inv;
+            // a ray parallel to a slab's faces gives NaN when it starts on one; keep it
+            if !t0.is_nan() && !t1.is_nan() {
+                near = near.max(t0.min(t1));
+                far = far.min(t0.max(t1));
+            }
+            if near > far {
+                return None;
+            }
+        }
+        Some(near)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    bounds: Aabb3<f64>,
+    /// The range of `Bvh::faces` under this node.
+    start: usize,
+    end: usize,
+    /// The first of two adjacent children, or `None` for a leaf.
+    children: Option<usize>,
+}
+
+/// The point of a mesh closest to a query point.
+#[derive(Copy, Clone, Debug)]
+pub struct ClosestFace {
+    /// Index of the face the point lies on, into `Polyhedron::faces`.
+    pub face: usize,
+    pub point: Point3<f64>,
+    pub distance: f64,
+}
+
+/// A bounding volume hierarchy over the faces of a `Polyhedron`, built with the surface area
+/// heuristic.
+///
+/// The hierarchy only stores face indices and boxes, so every query takes the mesh it was built
+/// from. After moving vertices through `Polyhedron::points_mut`, call `refit`; after changing the
+/// faces themselves, build a new one.
+#[derive(Clone, Debug)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    faces: Vec<usize>,
+}
+
+impl Bvh {
+    pub fn new<P: Vertex>(mesh: &Polyhedron<P>) -> Self {
+        let n = mesh.faces.len();
+        let bounds = (0..n)
+            .map(|f| Aabb3::from_points(&mesh.face_points(f)).unwrap_or_else(Aabb3::empty))
+            .collect::<Vec<_>>();
+        let centroids = bounds
+            .iter()
+            .map(|b| na::center(&b.min, &b.max))
+            .collect::<Vec<_>>();
+        let mut bvh = Bvh {
+            nodes: Vec::new(),
+            faces: (0..n).collect(),
+        };
+        if n == 0 {
+            return bvh;
+        }
+        bvh.nodes.push(Node {
+            bounds: bounds.iter().fold(Aabb3::empty(), |a, b| a.union(b)),
+            start: 0,
+            end: n,
+            children: None,
+        });
+        let mut todo = vec![0];
+        while let Some(i) = todo.pop() {
+            let (start, end) = (bvh.nodes[i].start, bvh.nodes[i].end);
+            let faces = &mut bvh.faces[start..end];
+            let mid = match split(faces, &bounds, &centroids, &bvh.nodes[i].bounds) {
+                Some(mid) => start + mid,
+                None => continue,
+            };
+            let child = bvh.nodes.len();
+            for &(start, end) in &[(start, mid), (mid, end)] {
+                bvh.nodes.push(Node {
+                    bounds: bvh.faces[start..end]
+                        .iter()
+                        .fold(Aabb3::empty(), |a, &f| a.union(&bounds[f])),
+                    start,
+                    end,
+                    children: None,
+                });
+            }
+            bvh.nodes[i].children = Some(child);
+            todo.push(child);
+            todo.push(child + 1);
+        }
+        bvh
+    }
+
+    /// Recomputes every box from the mesh's current vertex positions, keeping the tree's shape.
+    ///
+    /// This is much cheaper than rebuilding, but queries slow down as the mesh moves further from
+    /// the shape it was built for.
+    pub fn refit<P: Vertex>(&mut self, mesh: &Polyhedron<P>) {
+        // children always come after their parent
+        for i in (0..self.nodes.len()).rev() {
+            let node = &self.nodes[i];
+            let bounds = match node.children {
+                Some(c) => self.nodes[c].bounds.union(&self.nodes[c + 1].bounds),
+                None => self.faces[node.start..node.end]
+                    .iter()
+                    .fold(Aabb3::empty(), |a, &f| {
+                        a.union(
+                            &Aabb3::from_points(&mesh.face_points(f)).unwrap_or_else(Aabb3::empty),
+                        )
+                    }),
+            };
+            self.nodes[i].bounds = bounds;
+        }
+    }
+
+    /// Finds the nearest face this ray hits; see `Polyhedron::raycast`.
+    pub fn raycast<P: Vertex>(
+        &self,
+        mesh: &Polyhedron<P>,
+        ray: &Ray3,
+        cull_backfaces: bool,
+    ) -> Option<RayHit> {
+        let mut res: Option<RayHit> = None;
+        let mut todo = self.root().collect::<Vec<_>>();
+        while let Some(i) = todo.pop() {
+            let node = &self.nodes[i];
+            let limit = res.as_ref().map_or(f64::INFINITY, |r| r.distance);
+            if node.bounds.entry(ray, limit).is_none() {
+                continue;
+            }
+            match node.children {
+                Some(c) => {
+                    let near = |i: usize| self.nodes[i].bounds.entry(ray, limit);
+                    // visit the nearer child first, so that it can prune the other
+                    let (a, b) = (near(c), near(c + 1));
+                    if a.is_some() && b.is_some() && a < b {
+                        todo.push(c + 1);
+                        todo.push(c);
+                    } else {
+                        todo.extend(a.map(|_| c));
+                        todo.extend(b.map(|_| c + 1));
+                    }
+                }
+                None => {
+                    for &face in &self.faces[node.start..node.end] {
+                        let tri = mesh.face_points(face);
+                        if let Some(hit) = ray.intersect_triangle(&tri, cull_backfaces) {
+                            if res.as_ref().map_or(true, |r| hit.distance < r.distance) {
+                                res = Some(RayHit::new(face, &tri, &hit));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        res
+    }
+
+    /// Finds the point on the mesh's surface closest to `p`, or `None` if the mesh has no faces.
+    pub fn closest_point<P: Vertex>(
+        &self,
+        mesh: &Polyhedron<P>,
+        p: &Point3<f64>,
+    ) -> Option<ClosestFace> {
+        let mut res: Option<(usize, Point3<f64>, f64)> = None;
+        let mut todo = self.root().collect::<Vec<_>>();
+        while let Some(i) = todo.pop() {
+            let node = &self.nodes[i];
+            let limit = res.map_or(f64::INFINITY, |r| r.2);
+            if node.bounds.distance_squared(p) > limit {
+                continue;
+            }
+            match node.children {
+                Some(c) => {
+                    let dist = |i: usize| self.nodes[i].bounds.distance_squared(p);
+                    if dist(c) < dist(c + 1) {
+                        todo.push(c + 1);
+                        todo.push(c);
+                    } else {
+                        todo.push(c);
+                        todo.push(c + 1);
+                    }
+                }
+                None => {
+                    for &face in &self.faces[node.start..node.end] {
+                        let q = closest_point_on_triangle(p, &mesh.face_points(face));
+                        let dist = na::distance_squared(p, &q);
+                        if res.map_or(true, |r| dist < r.2) {
+                            res = Some((face, q, dist));
+                        }
+                    }
+                }
+            }
+        }
+        res.map(|(face, point, dist)| ClosestFace {
+            face,
+            point,
+            distance: dist.sqrt(),
+        })
+    }
+
+    /// Every pair of faces, one from each mesh, that touch or intersect; see
+    /// `triangles_intersect`. Pairs are given as `[self's face, other's face]`.
+    pub fn overlaps<P: Vertex, Q: Vertex>(
+        &self,
+        mesh: &Polyhedron<P>,
+        other: &Bvh,
+        other_mesh: &Polyhedron<Q>,
+    ) -> Vec<[usize; 2]> {
+        let mut res = Vec::new();
+        self.visit_overlaps(mesh, other, other_mesh, |pair| {
+            res.push(pair);
+            true
+        });
+        res
+    }
+
+    /// Whether any faces of the two meshes touch or intersect; stops at the first that do.
+    pub fn intersects<P: Vertex, Q: Vertex>(
+        &self,
+        mesh: &Polyhedron<P>,
+        other: &Bvh,
+        other_mesh: &Polyhedron<Q>,
+    ) -> bool {
+        let mut res = false;
+        self.visit_overlaps(mesh, other, other_mesh, |_| {
+            res = true;
+            false
+        });
+        res
+    }
+
+    fn root(&self) -> impl Iterator<Item = usize> {
+        if self.nodes.is_empty() {
+            None.into_iter()
+        } else {
+            Some(0).into_iter()
+        }
+    }
+
+    /// Walks both trees together, handing each intersecting face pair to `visit` until it returns
+    /// false.
+    fn visit_overlaps<P: Vertex, Q: Vertex, F: FnMut([usize; 2]) -> bool>(
+        &self,
+        mesh: &Polyhedron<P>,
+        other: &Bvh,
+        other_mesh: &Polyhedron<Q>,
+        mut visit: F,
+    ) {
+        let mut todo = self
+            .root()
+            .flat_map(|a| other.root().map(move |b| (a, b)))
+            .collect::<Vec<_>>();
+        while let Some((a, b)) = todo.pop() {
+            let (na, nb) = (&self.nodes[a], &other.nodes[b]);
+            if !na.bounds.overlaps(&nb.bounds) {
+                continue;
+            }
+            let split_a = match (na.children, nb.children) {
+                (None, None) => {
+                    for &fa in &self.faces[na.start..na.end] {
+                        let ta = mesh.face_points(fa);
+                        for &fb in &other.faces[nb.start..nb.end] {
+                            if triangles_intersect(&ta, &other_mesh.face_points(fb))
+                                && !visit([fa, fb])
+                            {
+                                return;
+                            }
+                        }
+                    }
+                    continue;
+                }
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                // descend into the bigger box, so both sides shrink at a similar pace
+                (Some(_), Some(_)) => na.bounds.half_area() >= nb.bounds.half_area(),
+            };
+            if let (true, Some(c)) = (split_a, na.children) {
+                todo.push((c, b));
+                todo.push((c + 1, b));
+            } else if let Some(c) = nb.children {
+                todo.push((a, c));
+                todo.push((a, c + 1));
+            }
+        }
+    }
+}
+
+/// Chooses where to split a node's faces by binned surface area heuristic, reordering them so that
+/// the first `mid` go in the first child. Returns `None` when the node should stay a leaf.
+fn split(
+    faces: &mut [usize],
+    bounds: &[Aabb3<f64>],
+    centroids: &[Point3<f64>],
+    node: &Aabb3<f64>,
+) -> Option<usize> {
+    if faces.len() <= MIN_SPLIT {
+        return None;
+    }
+    let spread = Aabb3::from_points(&faces.iter().map(|&f| centroids[f]).collect::<Vec<_>>())
+        .unwrap_or_else(Aabb3::empty);
+    let bin = |axis: usize, f: usize| {
+        let extent = spread.max[axis] - spread.min[axis];
+        let i = ((centroids[f][axis] - spread.min[axis]) / extent * BINS as f64) as usize;
+        i.min(BINS - 1)
+    };
+    // (cost, axis, number of bins on the first side)
+    let mut best: Option<(f64, usize, usize)> = None;
+    for axis in 0..3 {
+        if spread.max[axis] <= spread.min[axis] {
+            continue;
+        }
+        let mut bins = [(Aabb3::empty(), 0usize); BINS];
+        for &f in faces.iter() {
+            let b = &mut bins[bin(axis, f)];
+            *b = (b.0.union(&bounds[f]), b.1 + 1);
+        }
+        // sweep from the right to get the cost of everything past each split
+        let mut right = [0.0; BINS];
+        let mut acc = (Aabb3::empty(), 0usize);
+        for i in (1..BINS).rev() {
+            acc = (acc.0.union(&bins[i].0), acc.1 + bins[i].1);
+            right[i] = acc.0.half_area() * acc.1 as f64;
+        }
+        let mut acc = (Aabb3::empty(), 0usize);
+        for i in 1..BINS {
+            acc = (acc.0.union(&bins[i - 1].0), acc.1 + bins[i - 1].1);
+            let cost = acc.0.half_area() * acc.1 as f64 + right[i];
+            if best.map_or(true, |b| cost < b.0) {
+                best = Some((cost, axis, i));
+            }
+        }
+    }
+    // every centroid coincides; no axis can tell the faces apart
+    let (cost, axis, split) = best?;
+    // one traversal step against testing every face in the leaf, relative to the node's area
+    let area = node.half_area();
+    let split_cost = 1.0 + if area > 0.0 { cost / area } else { 0.0 };
+    if split_cost >= faces.len() as f64 && faces.len() <= MAX_LEAF {
+        return None;
+    }
+    let mut mid = 0;
+    for i in 0..faces.len() {
+        if bin(axis, faces[i]) < split {
+            faces.swap(i, mid);
+            mid += 1;
+        }
+    }
+    if mid == 0 || mid == faces.len() {
+        // the heuristic chose an empty side; fall back to halving
+        mid = faces.len() / 2;
+    }
+    Some(mid)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use na::Vector3;
+
+    /// A bumpy grid of `n` by `n` quads.
+    fn terrain(n: usize) -> Polyhedron<Point3<f64>> {
+        let mut mesh = Polyhedron {
+            points: Vec::new(),
+            faces: Vec::new(),
+        };
+        for y in 0..=n {
+            for x in 0..=n {
+                let (fx, fy) = (x as f64, y as f64);
+                mesh.points
+                    .push(Point3::new(fx, fy, (fx * 0.7).sin() + (fy * 0.3).cos()));
+            }
+        }
+        let id = |x: usize, y: usize| (y * (n + 1) + x) as u16;
+        for y in 0..n {
+            for x in 0..n {
+                mesh.faces.push([id(x, y), id(x + 1, y), id(x + 1, y + 1)]);
+                mesh.faces.push([id(x + 1, y + 1), id(x, y + 1), id(x, y)]);
+            }
+        }
+        mesh
+    }
+
+    fn rays() -> Vec<Ray3> {
+        (0..200)
+            .map(|i| {
+                let f = i as f64;
+                Ray3::towards(
+                    Point3::new((f * 0.37) % 24.0 - 2.0, (f * 0.61) % 24.0 - 2.0, 5.0),
+                    &Point3::new((f * 0.83) % 20.0, (f * 0.29) % 20.0, -1.0),
+                )
+            })
+            .collect()
+    }
+
+    fn same_hit(a: Option<RayHit>, b: Option<RayHit>) {
+        match (a, b) {
+            (Some(a), Some(b)) => assert_relative_eq!(a.distance, b.distance),
+            (None, None) => {}
+            (a, b) => panic!("{:?} != {:?}", a, b),
+        }
+    }
+
+    #[test]
+    fn raycast() {
+        let mesh = terrain(20);
+        let bvh = Bvh::new(&mesh);
+        assert!(bvh.nodes.len() > 1);
+        let mut hits = 0;
+        for ray in rays() {
+            let hit = bvh.raycast(&mesh, &ray, false);
+            hits += hit.is_some() as usize;
+            same_hit(hit, mesh.raycast(&ray, false));
+            same_hit(bvh.raycast(&mesh, &ray, true), mesh.raycast(&ray, true));
+        }
+        assert!(hits > 100);
+    }
+
+    #[test]
+    fn closest_point() {
+        let mesh = terrain(10);
+        let bvh = Bvh::new(&mesh);
+        for i in 0..50 {
+            let f = i as f64;
+            let p = Point3::new(
+                (f * 0.37) % 14.0 - 2.0,
+                (f * 0.61) % 14.0 - 2.0,
+                f % 5.0 - 2.0,
+            );
+            let res = bvh.closest_point(&mesh, &p).unwrap();
+            let brute = (0..mesh.faces.len())
+                .map(|f| na::distance(&p, &closest_point_on_triangle(&p, &mesh.face_points(f))))
+                .fold(f64::INFINITY, f64::min);
+            assert_relative_eq!(res.distance, brute);
+            assert_relative_eq!(na::distance(&p, &res.point), res.distance);
+        }
+    }
+
+    #[test]
+    fn refit() {
+        let mut mesh = terrain(10);
+        let mut bvh = Bvh::new(&mesh);
+        for p in mesh.points_mut() {
+            *p += Vector3::new(0.0, 0.0, p.x * 0.5);
+        }
+        bvh.refit(&mesh);
+        for ray in rays() {
+            same_hit(bvh.raycast(&mesh, &ray, false), mesh.raycast(&ray, false));
+        }
+    }
+
+    #[test]
+    fn overlaps() {
+        let cube = Polyhedron::cube();
+        let bvh = Bvh::new(&cube);
+        let moved = cube.clone().map(|p| [p[0] + 0.75, p[1] + 0.25, p[2]]);
+        let moved_bvh = Bvh::new(&moved);
+        let pairs = bvh.overlaps(&cube, &moved_bvh, &moved);
+        assert!(!pairs.is_empty());
+        for &[a, b] in &pairs {
+            assert!(triangles_intersect(
+                &cube.face_points(a),
+                &moved.face_points(b)
+            ));
+        }
+        let brute = (0..12)
+            .flat_map(|a| (0..12).map(move |b| (a, b)))
+            .filter(|&(a, b)| triangles_intersect(&cube.face_points(a), &moved.face_points(b)))
+            .count();
+        assert_eq!(pairs.len(), brute);
+        assert!(bvh.intersects(&cube, &moved_bvh, &moved));
+        let far = cube.clone().map(|p| [p[0] + 2.0, p[1], p[2]]);
+        assert!(!bvh.intersects(&cube, &Bvh::new(&far), &far));
+    }
+
+    #[test]
+    fn empty() {
+        let mesh: Polyhedron<Point3<f64>> = Polyhedron {
+            points: Vec::new(),
+            faces: Vec::new(),
+        };
+        let bvh = Bvh::new(&mesh);
+        let ray = Ray3::new(Point3::origin(), Vector3::x());
+        assert!(bvh.raycast(&mesh, &ray, false).is_none());
+        assert!(bvh.closest_point(&mesh, &Point3::origin()).is_none());
+    }
+}
@@ -0,0 +1,193 @@
+//! Clipping in homogeneous clip space, after projection and before the divide by `w`.
+//!
+//! The view frustum is where `-w <= x, y, z <= w`, as produced by `Camera::fresh_mat`. Clipping
+//! here rather than after the divide keeps triangles that cross the camera's plane from wrapping
+//! around through infinity.
+
+use super::polyhedron::*;
+use na::{Point3, Point4, Vector4};
+
+/// The frustum's planes as `(x, y, z, w)` coefficients, each positive on its inside. The sides
+/// come first, so that `guard_band` can widen them.
+fn planes(guard_band: Option<f64>) -> [Vector4<f64>; 6] {
+    let g = guard_band.unwrap_or(1.0);
+    [
+        Vector4::new(1.0, 0.0, 0.0, g),
+        Vector4::new(-1.0, 0.0, 0.0, g),
+        Vector4::new(0.0, 1.0, 0.0, g),
+        Vector4::new(0.0, -1.0, 0.0, g),
+        Vector4::new(0.0, 0.0, 1.0, 1.0),
+        Vector4::new(0.0, 0.0, -1.0, 1.0),
+    ]
+}
+
+/// Clips a convex polygon to the view frustum (Sutherland–Hodgman), keeping its winding.
+///
+/// With a `guard_band` of `Some(g)`, the left, right, top and bottom planes are pushed out to
+/// `-g * w <= x, y <= g * w`, leaving the rasterizer to trim the sides for the cost of fewer new
+/// vertices; the near and far planes are always exact. Returns an empty polygon if nothing is left.
+pub fn clip_polygon(polygon: &[Point4<f64>], guard_band: Option<f64>) -> Vec<Point4<f64>> {
+    let mut res = polygon.to_vec();
+    for plane in planes(guard_band).iter() {
+        if res.is_empty() {
+            break;
+        }
+        let input = std::mem::take(&mut res);
+        for (i, p) in input.iter().enumerate() {
+            let q = input[(i + 1) % input.len()];
+            let (dp, dq) = (plane.dot(&p.coords), plane.dot(&q.coords));
+            if dp >= 0.0 {
+                res.push(*p);
+            }
+            if (dp >= 0.0) != (dq >= 0.0) {
+                // interpolating before the divide is what keeps this exact under perspective
+                let t = dp / (dp - dq);
+                res.push(p + (q - p) * t);
+            }
+        }
+    }
+    res
+}
+
+/// Clips a triangle to the view frustum; see `clip_polygon`.
+///
+/// The clipped polygon is split into a fan of triangles with the same winding, so a triangle
+/// entirely inside comes back unchanged.
+pub fn clip_triangle(tri: &[Point4<f64>; 3], guard_band: Option<f64>) -> Vec<[Point4<f64>; 3]> {
+    let poly = clip_polygon(tri, guard_band);
+    (2..poly.len().max(2))
+        .map(|i| [poly[0], poly[i - 1], poly[i]])
+        .collect()
+}
+
+/// Drops a clip-space point's depth, leaving the homogeneous `(x, y, w)` that `ndc_to_screen`
+/// takes.
+pub fn xyw(p: &Point4<f64>) -> Point3<f64> {
+    Point3::new(p.x, p.y, p.w)
+}
+
+impl Polyhedron<Point4<f64>> {
+    /// Clips every face of a mesh already transformed into clip space; see `clip_polygon`.
+    ///
+    /// Faces entirely inside keep their vertices, faces entirely outside any plane are dropped,
+    /// and the rest are re-triangulated around new vertices appended to `points`.
+    ///
+    /// # Panics
+    /// If clipping adds more vertices than a `u16` can index.
+    pub fn clip_frustum(&self, guard_band: Option<f64>) -> Polyhedron<Point4<f64>> {
+        let planes = planes(guard_band);
+        let mut res = Polyhedron {
+            points: self.points.clone(),
+            faces: Vec::with_capacity(self.faces.len()),
+        };
+        for face in &self.faces {
+            let tri = [
+                self.points[face[0] as usize],
+                self.points[face[1] as usize],
+                self.points[face[2] as usize],
+            ];
+            let inside = |p: &Point4<f64>, plane: &Vector4<f64>| plane.dot(&p.coords) >= 0.0;
+            if planes.iter().any(|pl| !tri.iter().any(|p| inside(p, pl))) {
+                continue;
+            }
+            if planes.iter().all(|pl| tri.iter().all(|p| inside(p, pl))) {
+                res.faces.push(*face);
+                continue;
+            }
+            let poly = clip_polygon(&tri, guard_band);
+            let first = res.points.len();
+            assert!(
+                first + poly.len() <= u16::MAX as usize + 1,
+                "Too many vertices after clipping: {}",
+                first + poly.len()
+            );
+            res.points.extend(poly.iter().cloned());
+            for i in 2..poly.len() {
+                res.faces
+                    .push([first as u16, (first + i - 1) as u16, (first + i) as u16]);
+            }
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn within(p: &Point4<f64>, g: f64) -> bool {
+        let eps = 1.0e-12;
+        p.x.abs() <= g * p.w + eps && p.y.abs() <= g * p.w + eps && p.z.abs() <= p.w + eps
+    }
+
+    #[test]
+    fn inside() {
+        let tri = [
+            Point4::new(-0.5, -0.5, 0.0, 1.0),
+            Point4::new(0.5, -0.5, 0.0, 1.0),
+            Point4::new(0.0, 0.5, 0.5, 1.0),
+        ];
+        assert_eq!(clip_triangle(&tri, None), vec![tri]);
+    }
+
+    #[test]
+    fn outside() {
+        let tri = [
+            Point4::new(2.0, 0.0, 0.0, 1.0),
+            Point4::new(3.0, 0.0, 0.0, 1.0),
+            Point4::new(2.0, 1.0, 0.0, 1.0),
+        ];
+        assert!(clip_triangle(&tri, None).is_empty());
+    }
+
+    #[test]
+    fn near_plane() {
+        // one corner behind the camera, where w is negative
+        let tri = [
+            Point4::new(0.0, 0.0, -3.0, -1.0),
+            Point4::new(-0.5, 0.0, 0.0, 1.0),
+            Point4::new(0.5, 0.0, 0.0, 1.0),
+        ];
+        let res = clip_triangle(&tri, None);
+        assert_eq!(res.len(), 2);
+        for t in &res {
+            assert!(t.iter().all(|p| within(p, 1.0) && p.w > 0.0));
+        }
+    }
+
+    #[test]
+    fn guard_band() {
+        let tri = [
+            Point4::new(-1.5, -0.5, 0.0, 1.0),
+            Point4::new(1.5, -0.5, 0.0, 1.0),
+            Point4::new(0.0, 0.5, 0.0, 1.0),
+        ];
+        let exact = clip_triangle(&tri, None);
+        assert!(exact.len() > 1);
+        assert!(exact.iter().flatten().all(|p| within(p, 1.0)));
+        assert_eq!(clip_triangle(&tri, Some(2.0)), vec![tri]);
+        let clipped = clip_triangle(&tri, Some(1.25));
+        assert!(clipped.iter().flatten().all(|p| within(p, 1.25)));
+    }
+
+    #[test]
+    fn mesh() {
+        let mesh = Polyhedron::<[f32; 3]>::quad()
+            .map(|p| Point4::new(f64::from(p[0]) * 3.0, f64::from(p[2]) * 3.0, 0.0, 1.0));
+        let res = mesh.clip_frustum(None);
+        assert!(!res.faces.is_empty());
+        let area = res
+            .faces
+            .iter()
+            .map(|f| {
+                let p = |i: u16| xyw(&res.points[i as usize]).xy();
+                let (a, b, c) = (p(f[0]), p(f[1]), p(f[2]));
+                (b - a).perp(&(c - a)) / 2.0
+            })
+            .sum::<f64>();
+        assert_relative_eq!(area.abs(), 4.0, epsilon = 1.0e-9);
+        for f in &res.faces {
+            assert!(f.iter().all(|&i| within(&res.points[i as usize], 1.0)));
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! The view frustum as planes in the world, for skipping whatever the camera can't see before it
+//! is projected at all.
+
+use super::polyhedron::*;
+use super::*;
+use na::{Matrix4, Vector4};
+
+/// Where a shape lies relative to a frustum.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Visibility {
+    Inside,
+    Outside,
+    /// Partly inside, or close enough to a corner of the frustum that the tests can't tell.
+    Intersecting,
+}
+
+/// A convex volume bounded by six planes, all facing inwards.
+#[derive(Copy, Clone, Debug)]
+pub struct Frustum {
+    /// In the order left, right, bottom, top, near, far.
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    /// The frustum a view-projection matrix maps onto `-w <= x, y, z <= w`, in the space the
+    /// matrix maps from (Gribb and Hartmann's method).
+    pub fn from_matrix(m: &Matrix4<f64>) -> Self {
+        let row = |i: usize| m.row(i).transpose();
+        let plane = |v: Vector4<f64>| {
+            let normal = v.xyz();
+            Plane::new(
+                Point3::from(normal * (-v.w / normal.norm_squared())),
+                normal,
+            )
+        };
+        Frustum {
+            planes: [
+                plane(row(3) + row(0)),
+                plane(row(3) - row(0)),
+                plane(row(3) + row(1)),
+                plane(row(3) - row(1)),
+                plane(row(3) + row(2)),
+                plane(row(3) - row(2)),
+            ],
+        }
+    }
+
+    /// Whether the point is inside or on the boundary.
+    pub fn contains_point(&self, p: &Point3<f64>) -> bool {
+        self.planes.iter().all(|plane| plane.distance(p) >= 0.0)
+    }
+
+    pub fn test_sphere(&self, center: &Point3<f64>, radius: f64) -> Visibility {
+        let mut res = Visibility::Inside;
+        for plane in &self.planes {
+            let d = plane.distance(center);
+            if d < -radius {
+                return Visibility::Outside;
+            }
+            if d < radius {
+                res = Visibility::Intersecting;
+            }
+        }
+        res
+    }
+
+    /// Tests a box against each plane in turn, by its corners furthest in front of and behind it.
+    pub fn test_aabb(&self, aabb: &Aabb3<f64>) -> Visibility {
+        let mut res = Visibility::Inside;
+        for plane in &self.planes {
+            let corner = |front: bool| {
+                Point3::from(aabb.min.coords.zip_zip_map(
+                    &aabb.max.coords,
+                    &plane.normal,
+                    |lo, hi, n| if (n >= 0.0) == front { hi } else { lo },
+                ))
+            };
+            if plane.distance(&corner(true)) < 0.0 {
+                return Visibility::Outside;
+            }
+            if plane.distance(&corner(false)) < 0.0 {
+                res = Visibility::Intersecting;
+            }
+        }
+        res
+    }
+
+    /// Tests a mesh by its bounding box. A mesh without points is always outside.
+    pub fn test_polyhedron<P: Vertex>(&self, mesh: &Polyhedron<P>) -> Visibility {
+        match mesh.bounds() {
+            Some(bounds) => self.test_aabb(&bounds),
+            None => Visibility::Outside,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use na::{Orthographic3, Perspective3};
+
+    fn frustum() -> Frustum {
+        // looking down -z from the origin, seeing z from -1 to -10 and x, y within |z|
+        let proj = Perspective3::new(1.0, std::f64::consts::FRAC_PI_2, 1.0, 10.0);
+        Frustum::from_matrix(proj.as_matrix())
+    }
+
+    #[test]
+    fn points() {
+        let f = frustum();
+        assert!(f.contains_point(&Point3::new(0.0, 0.0, -5.0)));
+        assert!(f.contains_point(&Point3::new(4.0, -4.0, -5.0)));
+        assert!(!f.contains_point(&Point3::new(6.0, 0.0, -5.0)));
+        assert!(!f.contains_point(&Point3::new(0.0, 0.0, 5.0)));
+        assert!(!f.contains_point(&Point3::new(0.0, 0.0, -0.5)));
+        assert!(!f.contains_point(&Point3::new(0.0, 0.0, -11.0)));
+        assert_relative_eq!(f.planes[4].distance(&Point3::new(0.0, 0.0, -3.0)), 2.0);
+        assert_relative_eq!(
+            f.planes[0].distance(&Point3::new(0.0, 0.0, -2.0)),
+            2.0f64.sqrt(),
+            epsilon = 1.0e-9
+        );
+    }
+
+    #[test]
+    fn spheres() {
+        let f = frustum();
+        let test = |x: f64, z: f64, r: f64| f.test_sphere(&Point3::new(x, 0.0, z), r);
+        assert_eq!(test(0.0, -5.0, 1.0), Visibility::Inside);
+        assert_eq!(test(0.0, -9.5, 1.0), Visibility::Intersecting);
+        assert_eq!(test(0.0, 5.0, 1.0), Visibility::Outside);
+        assert_eq!(test(8.0, -5.0, 1.0), Visibility::Outside);
+    }
+
+    #[test]
+    fn boxes() {
+        let f = frustum();
+        let test =
+            |min: [f64; 3], max: [f64; 3]| f.test_aabb(&Aabb3::new(&min.into(), &max.into()));
+        assert_eq!(
+            test([-1.0, -1.0, -6.0], [1.0, 1.0, -4.0]),
+            Visibility::Inside
+        );
+        assert_eq!(
+            test([-1.0, -1.0, -1.5], [1.0, 1.0, 0.5]),
+            Visibility::Intersecting
+        );
+        assert_eq!(
+            test([-1.0, -1.0, 1.0], [1.0, 1.0, 3.0]),
+            Visibility::Outside
+        );
+        assert_eq!(
+            test([6.0, -1.0, -5.0], [7.0, 1.0, -4.0]),
+            Visibility::Outside
+        );
+        // straddles the whole frustum without any corner inside it
+        assert_eq!(
+            test([-20.0, -20.0, -5.0], [20.0, 20.0, -4.0]),
+            Visibility::Intersecting
+        );
+    }
+
+    #[test]
+    fn meshes() {
+        let f = frustum();
+        // the near plane cuts through this one
+        let near = Polyhedron::cube().map(|p| [p[0], p[1], p[2] - 1.0]);
+        assert_eq!(f.test_polyhedron(&near), Visibility::Intersecting);
+        let ahead = Polyhedron::cube().map(|p| [p[0], p[1], p[2] - 5.0]);
+        assert_eq!(f.test_polyhedron(&ahead), Visibility::Inside);
+        let behind = Polyhedron::cube().map(|p| [p[0], p[1], p[2] + 5.0]);
+        assert_eq!(f.test_polyhedron(&behind), Visibility::Outside);
+        let ortho = Orthographic3::new(-1.0, 1.0, -1.0, 1.0, 0.1, 10.0);
+        let f = Frustum::from_matrix(ortho.as_matrix());
+        assert_eq!(f.test_polyhedron(&behind), Visibility::Outside);
+        let far = Polyhedron::cube().map(|p| [p[0] * 0.5, p[1] * 0.5, p[2] - 9.0]);
+        assert_eq!(f.test_polyhedron(&far), Visibility::Inside);
+    }
+}
@@ -0,0 +1,357 @@
+//! Distance and penetration queries between convex shapes, by GJK and EPA.
+//!
+//! Both work on the Minkowski difference of the two shapes, which they only ever see through
+//! `SupportMap`, so any convex shape that can report its furthest point in a direction works.
+
+use super::aabb::*;
+use super::polyhedron::*;
+use super::triangle_weights;
+use collision::{self, combine, Gjk, MAX_ITERATIONS, TOLERANCE};
+use na::{Point3, Unit, UnitQuaternion, Vector3};
+
+type Vert = collision::Vert<Vector3<f64>>;
+
+/// A convex shape, described by its furthest point in any direction.
+pub trait SupportMap {
+    /// A point of the shape as far along `dir` as possible. `dir` need not be normalized.
+    fn support(&self, dir: &Vector3<f64>) -> Point3<f64>;
+}
+
+/// Treats the mesh as its convex hull.
+impl<P: Vertex> SupportMap for Polyhedron<P> {
+    fn support(&self, dir: &Vector3<f64>) -> Point3<f64> {
+        self.points
+            .iter()
+            .map(Vertex::position)
+            .max_by(|l, r| l.coords.dot(dir).partial_cmp(&r.coords.dot(dir)).unwrap())
+            .unwrap_or_else(Point3::origin)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Sphere {
+    pub center: Point3<f64>,
+    pub radius: f64,
+}
+
+impl SupportMap for Sphere {
+    fn support(&self, dir: &Vector3<f64>) -> Point3<f64> {
+        let dir = Unit::try_new(*dir, 0.0).unwrap_or_else(Vector3::x_axis);
+        self.center + dir.as_ref() * self.radius
+    }
+}
+
+/// A box, which unlike `Polyhedron::cube` may be turned any way.
+#[derive(Copy, Clone, Debug)]
+pub struct Cuboid {
+    pub center: Point3<f64>,
+    /// Half the box's size along each of its own axes.
+    pub half_extents: Vector3<f64>,
+    pub orientation: UnitQuaternion<f64>,
+}
+
+impl SupportMap for Cuboid {
+    fn support(&self, dir: &Vector3<f64>) -> Point3<f64> {
+        let local = self.orientation.inverse() * dir;
+        let corner = self
+            .half_extents
+            .zip_map(&local, |h, d| if d < 0.0 { -h } else { h });
+        self.center + self.orientation * corner
+    }
+}
+
+impl SupportMap for Aabb3<f64> {
+    fn support(&self, dir: &Vector3<f64>) -> Point3<f64> {
+        Point3::from(
+            self.min.coords.zip_zip_map(
+                &self.max.coords,
+                dir,
+                |lo, hi, d| if d < 0.0 { lo } else { hi },
+            ),
+        )
+    }
+}
+
+/// The closest points of two shapes that don't overlap; see `collision::Separation`.
+pub type Separation = collision::Separation<Vector3<f64>>;
+
+/// How two shapes overlap; see `collision::Contact`.
+pub type Contact = collision::Contact<Vector3<f64>>;
+
+fn support<A: SupportMap, B: SupportMap>(a: &A, b: &B, dir: &Vector3<f64>) -> Vert {
+    Vert::new(a.support(dir), b.support(&-dir))
+}
+
+/// The barycentric weights of the point of a triangle or tetrahedron closest to the origin, or
+/// `None` if the simplex is a tetrahedron containing it.
+fn closest(simplex: &[Vert]) -> Option<Vec<f64>> {
+    let origin = Point3::origin();
+    let tri = |i: usize, j: usize, k: usize| {
+        [
+            Point3::from(simplex[i].p),
+            Point3::from(simplex[j].p),
+            Point3::from(simplex[k].p),
+        ]
+    };
+    match simplex.len() {
+        3 => Some(triangle_weights(&origin, &tri(0, 1, 2)).to_vec()),
+        _ => {
+            const FACES: [[usize; 4]; 4] = [[0, 1, 2, 3], [0, 1, 3, 2], [0, 2, 3, 1], [1, 2, 3, 0]];
+            let mut best: Option<(f64, Vec<f64>)> = None;
+            let mut inside = true;
+            for &[i, j, k, l] in &FACES {
+                let n = (simplex[j].p - simplex[i].p).cross(&(simplex[k].p - simplex[i].p));
+                let (side, other) = (n.dot(&-simplex[i].p), n.dot(&(simplex[l].p - simplex[i].p)));
+                // the origin is past this face if it's on the side away from the fourth vertex
+                if side * other > 0.0 {
+                    continue;
+                }
+                inside = false;
+                let w = triangle_weights(&origin, &tri(i, j, k));
+                let mut weights = vec![0.0; 4];
+                weights[i] = w[0];
+                weights[j] = w[1];
+                weights[k] = w[2];
+                let p = simplex[i].p * w[0] + simplex[j].p * w[1] + simplex[k].p * w[2];
+                if best.as_ref().map_or(true, |b| p.norm_squared() < b.0) {
+                    best = Some((p.norm_squared(), weights));
+                }
+            }
+            if inside {
+                None
+            } else {
+                best.map(|b| b.1)
+            }
+        }
+    }
+}
+
+fn gjk<A: SupportMap, B: SupportMap>(a: &A, b: &B) -> Gjk<Vector3<f64>> {
+    collision::gjk(|dir| support(a, b, dir), closest)
+}
+
+/// The closest points of two convex shapes, or `None` if they touch or overlap.
+pub fn distance<A: SupportMap, B: SupportMap>(a: &A, b: &B) -> Option<Separation> {
+    match gjk(a, b) {
+        Gjk::Separated(sep) => Some(sep),
+        Gjk::Overlapping(_) => None,
+    }
+}
+
+pub fn intersects<A: SupportMap, B: SupportMap>(a: &A, b: &B) -> bool {
+    distance(a, b).is_none()
+}
+
+/// How deeply two convex shapes overlap, or `None` if they don't.
+///
+/// Shapes that only touch, or that are too flat to enclose the origin in their Minkowski
+/// difference, overlap with a depth of 0.
+pub fn penetration<A: SupportMap, B: SupportMap>(a: &A, b: &B) -> Option<Contact> {
+    match gjk(a, b) {
+        Gjk::Separated(_) => None,
+        Gjk::Overlapping(simplex) => Some(epa(a, b, simplex)),
+    }
+}
+
+/// Grows a simplex around the origin into a tetrahedron, if the shapes have the volume for one.
+fn blow_up<A: SupportMap, B: SupportMap>(a: &A, b: &B, mut simplex: Vec<Vert>) -> Vec<Vert> {
+    let axes = [Vector3::x(), Vector3::y(), Vector3::z()];
+    let scale = simplex.iter().map(|s| s.p.norm()).fold(1.0, f64::max);
+    let eps = 1.0e-9 * scale;
+    // try each direction and its opposite, keeping the first vertex that adds a dimension
+    let extend = |simplex: &mut Vec<Vert>, dirs: &[Vector3<f64>]| {
+        for dir in dirs {
+            for dir in &[*dir, -dir] {
+                let v = support(a, b, dir);
+                let grows = match simplex.len() {
+                    1 => (v.p - simplex[0].p).norm() > eps,
+                    2 => {
+                        let d = simplex[1].p - simplex[0].p;
+                        d.cross(&(v.p - simplex[0].p)).norm() > eps * d.norm()
+                    }
+                    _ => {
+                        let n = (simplex[1].p - simplex[0].p).cross(&(simplex[2].p - simplex[0].p));
+                        n.dot(&(v.p - simplex[0].p)).abs() > eps * n.norm()
+                    }
+                };
+                if grows {
+                    simplex.push(v);
+                    return true;
+                }
+            }
+        }
+        false
+    };
+    if simplex.len() == 1 && !extend(&mut simplex, &axes) {
+        return simplex;
+    }
+    if simplex.len() == 2 {
+        let d = simplex[1].p - simplex[0].p;
+        let dirs = axes.iter().map(|e| d.cross(e)).collect::<Vec<_>>();
+        if !extend(&mut simplex, &dirs) {
+            return simplex;
+        }
+    }
+    if simplex.len() == 3 {
+        let n = (simplex[1].p - simplex[0].p).cross(&(simplex[2].p - simplex[0].p));
+        extend(&mut simplex, &[n]);
+    }
+    simplex
+}
+
+/// Expands the simplex around the origin towards the boundary of the Minkowski difference, to
+/// find the face nearest the origin (the expanding polytope algorithm).
+fn epa<A: SupportMap, B: SupportMap>(a: &A, b: &B, simplex: Vec<Vert>) -> Contact {
+    let mut verts = blow_up(a, b, simplex);
+    if verts.len() < 4 {
+        return Contact {
+            normal: Vector3::x_axis(),
+            depth: 0.0,
+            points: [verts[0].a, verts[0].b],
+        };
+    }
+    let mut faces = Vec::new();
+    for &[i, j, k, l] in &[[0, 1, 2, 3], [0, 1, 3, 2], [0, 2, 3, 1], [1, 2, 3, 0]] {
+        let n = (verts[j].p - verts[i].p).cross(&(verts[k].p - verts[i].p));
+        // wind every face to face away from the rest of the tetrahedron
+        if n.dot(&(verts[l].p - verts[i].p)) > 0.0 {
+            faces.push([i, k, j]);
+        } else {
+            faces.push([i, j, k]);
+        }
+    }
+    let normal = |verts: &[Vert], f: &[usize; 3]| {
+        Unit::try_new(
+            (verts[f[1]].p - verts[f[0]].p).cross(&(verts[f[2]].p - verts[f[0]].p)),
+            0.0,
+        )
+    };
+    let mut nearest = (faces[0], Vector3::x_axis(), f64::INFINITY);
+    for _ in 0..MAX_ITERATIONS {
+        nearest = (faces[0], Vector3::x_axis(), f64::INFINITY);
+        for f in &faces {
+            if let Some(n) = normal(&verts, f) {
+                let d = n.dot(&verts[f[0]].p);
+                if d < nearest.2 {
+                    nearest = (*f, n, d);
+                }
+            }
+        }
+        let (_, n, d) = nearest;
+        let next = support(a, b, &n);
+        if next.p.dot(&n) - d <= TOLERANCE * d.abs().max(1.0) {
+            break;
+        }
+        // carve away every face the new vertex can see, and fill the hole from it
+        let id = verts.len();
+        verts.push(next);
+        let (visible, kept): (Vec<[usize; 3]>, Vec<[usize; 3]>) = faces.iter().partition(|f| {
+            normal(&verts, f).map_or(false, |n| n.dot(&(next.p - verts[f[0]].p)) > 0.0)
+        });
+        if visible.is_empty() {
+            break;
+        }
+        let edges = visible
+            .iter()
+            .flat_map(|f| vec![(f[0], f[1]), (f[1], f[2]), (f[2], f[0])])
+            .collect::<Vec<_>>();
+        faces = kept;
+        for &(i, j) in &edges {
+            // edges shared by two visible faces are inside the hole; the rest ring it
+            if !edges.contains(&(j, i)) {
+                faces.push([i, j, id]);
+            }
+        }
+    }
+    let (f, n, d) = nearest;
+    let tri = [
+        Point3::from(verts[f[0]].p),
+        Point3::from(verts[f[1]].p),
+        Point3::from(verts[f[2]].p),
+    ];
+    let w = triangle_weights(&Point3::from(n.as_ref() * d), &tri);
+    Contact {
+        normal: n,
+        depth: d.max(0.0),
+        points: combine(&[verts[f[0]], verts[f[1]], verts[f[2]]], &w),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::sat::sat_collision;
+    use super::*;
+
+    #[test]
+    fn spheres() {
+        let a = Sphere {
+            center: Point3::new(0.0, 0.0, 0.0),
+            radius: 1.0,
+        };
+        let b = Sphere {
+            center: Point3::new(3.0, 4.0, 0.0),
+            radius: 2.0,
+        };
+        let sep = distance(&a, &b).unwrap();
+        assert_relative_eq!(sep.distance, 2.0, epsilon = 1.0e-6);
+        assert_relative_eq!(sep.points[0], Point3::new(0.6, 0.8, 0.0), epsilon = 1.0e-3);
+        assert_relative_eq!(sep.points[1], Point3::new(1.8, 2.4, 0.0), epsilon = 1.0e-3);
+        let c = Sphere {
+            center: Point3::new(0.0, 0.0, 2.5),
+            radius: 2.0,
+        };
+        assert!(intersects(&a, &c));
+        let contact = penetration(&a, &c).unwrap();
+        assert_relative_eq!(contact.depth, 0.5, epsilon = 1.0e-3);
+        assert_relative_eq!(contact.normal.into_inner(), Vector3::z(), epsilon = 1.0e-2);
+    }
+
+    #[test]
+    fn cubes() {
+        let a = cube_at(0.0, 0.0, 0.0);
+        let b = cube_at(2.0, 0.25, 0.0);
+        let sep = distance(&a, &b).unwrap();
+        assert_relative_eq!(sep.distance, 1.0, epsilon = 1.0e-9);
+        assert_relative_eq!(sep.points[0].x, 0.5, epsilon = 1.0e-9);
+        assert_relative_eq!(sep.points[1].x, 1.5, epsilon = 1.0e-9);
+        assert!(penetration(&a, &b).is_none());
+        let b = cube_at(0.2, 0.8, 0.1);
+        let contact = penetration(&a, &b).unwrap();
+        let sat = sat_collision(&a, &b).unwrap();
+        assert_relative_eq!(contact.depth, sat.depth, epsilon = 1.0e-9);
+        assert_relative_eq!(contact.depth, 0.2, epsilon = 1.0e-9);
+        assert_relative_eq!(contact.normal.into_inner(), Vector3::y(), epsilon = 1.0e-9);
+        assert_relative_eq!(contact.points[0].y, 0.5, epsilon = 1.0e-9);
+        assert_relative_eq!(contact.points[1].y, 0.3, epsilon = 1.0e-9);
+    }
+
+    #[test]
+    fn cuboid() {
+        let turned = Cuboid {
+            center: Point3::new(2.0, 0.0, 0.0),
+            half_extents: Vector3::new(0.5, 0.5, 0.5),
+            orientation: UnitQuaternion::from_axis_angle(
+                &Vector3::z_axis(),
+                std::f64::consts::FRAC_PI_4,
+            ),
+        };
+        // a corner points back at the cube
+        let sep = distance(&cube_at(0.0, 0.0, 0.0), &turned).unwrap();
+        assert_relative_eq!(sep.distance, 1.5 - 0.5f64.sqrt(), epsilon = 1.0e-9);
+        let aabb = Aabb3::new(&Point3::new(-0.5, -0.5, -0.5), &Point3::new(0.5, 0.5, 0.5));
+        let sep = distance(&aabb, &turned).unwrap();
+        assert_relative_eq!(sep.distance, 1.5 - 0.5f64.sqrt(), epsilon = 1.0e-9);
+        let sphere = Sphere {
+            center: Point3::new(2.0, 0.0, 0.0),
+            radius: 1.0,
+        };
+        let contact = penetration(&turned, &sphere).unwrap();
+        assert_relative_eq!(contact.depth, 1.0 + 0.5, epsilon = 1.0e-3);
+    }
+
+    #[test]
+    fn touching() {
+        let contact = penetration(&cube_at(0.0, 0.0, 0.0), &cube_at(1.0, 0.0, 0.0)).unwrap();
+        assert_relative_eq!(contact.depth, 0.0, epsilon = 1.0e-9);
+    }
+}
@@ -1,14 +1,349 @@
+use self::aabb::*;
 use super::*;
-use na::{Point3, Vector2};
+use na::{Point3, RealField, Unit, Vector2, Vector3};
 
+pub mod aabb;
+pub mod bvh;
+pub mod clip;
+pub mod frustum;
+pub mod gjk;
 pub mod polyhedron;
+pub mod ray;
+pub mod sat;
+pub mod slice;
 
 pub type Line3<N> = [Point3<N>; 2];
 
+/// How far a `Line3` extends past the two points that define it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LineKind {
+    /// Only between its points.
+    Segment,
+    /// From its first point on through its second, forever.
+    Ray,
+    /// Forever in both directions.
+    Line,
+}
+
+impl LineKind {
+    /// The range of parameters along the line, where 0 is its first point and 1 its second.
+    fn range(self) -> [f64; 2] {
+        match self {
+            LineKind::Segment => [0.0, 1.0],
+            LineKind::Ray => [0.0, f64::INFINITY],
+            LineKind::Line => [f64::NEG_INFINITY, f64::INFINITY],
+        }
+    }
+}
+
+/// An infinite plane, with a 2D frame of its own for moving points between it and `plane`.
+#[derive(Copy, Clone, Debug)]
+pub struct Plane {
+    /// A point on the plane, which is also the origin of its 2D frame.
+    pub origin: Point3<f64>,
+    /// Points to the plane's positive side.
+    pub normal: Unit<Vector3<f64>>,
+}
+
+impl Plane {
+    pub fn new(origin: Point3<f64>, normal: Vector3<f64>) -> Self {
+        Plane {
+            origin,
+            normal: Unit::new_normalize(normal),
+        }
+    }
+
+    /// The plane through three points, facing the side from which they appear counter-clockwise.
+    /// Returns `None` if the points are collinear.
+    pub fn from_points(a: &Point3<f64>, b: &Point3<f64>, c: &Point3<f64>) -> Option<Self> {
+        let normal = (b - a).cross(&(c - a));
+        Unit::try_new(normal, 0.0).map(|normal| Plane { origin: *a, normal })
+    }
+
+    /// How far `p` lies from the plane, negative behind it.
+    pub fn distance(&self, p: &Point3<f64>) -> f64 {
+        self.normal.dot(&(p - self.origin))
+    }
+
+    /// The closest point on the plane to `p`.
+    pub fn project(&self, p: &Point3<f64>) -> Point3<f64> {
+        p - self.normal.as_ref() * self.distance(p)
+    }
+
+    /// The x and y axes of the plane's 2D frame.
+    ///
+    /// They are chosen from the normal alone, and form a right-handed frame with it, so 2D shapes
+    /// wound counter-clockwise appear counter-clockwise from the plane's positive side. A plane
+    /// facing +z has the world's x and y axes.
+    pub fn axes(&self) -> [Unit<Vector3<f64>>; 2] {
+        let n = self.normal.as_ref();
+        let helper = if n.x.abs() < 0.9 {
+            Vector3::x()
+        } else {
+            Vector3::y()
+        };
+        let x = Unit::new_normalize(helper - n * n.dot(&helper));
+        [x, Unit::new_unchecked(n.cross(&x))]
+    }
+
+    /// Projects `p` onto the plane and gives its coordinates in the plane's 2D frame.
+    pub fn to_local(&self, p: &Point3<f64>) -> Point2<f64> {
+        let [x, y] = self.axes();
+        let d = p - self.origin;
+        Point2::new(x.dot(&d), y.dot(&d))
+    }
+
+    /// The point on the plane at these coordinates in its 2D frame.
+    pub fn from_local(&self, p: &Point2<f64>) -> Point3<f64> {
+        let [x, y] = self.axes();
+        self.origin + x.as_ref() * p.x + y.as_ref() * p.y
+    }
+}
+
+/// The closest approach between two lines.
+#[derive(Copy, Clone, Debug)]
+pub struct ClosestPoints {
+    /// The closest point on each line.
+    pub points: [Point3<f64>; 2],
+    /// How far along each line its point lies, where 0 is its first point and 1 its second.
+    pub params: [f64; 2],
+    pub distance: f64,
+}
+
+/// Finds the closest points between two segments, rays or infinite lines.
+///
+/// When the closest points aren't unique, as with parallel lines, the ones closest to the start of
+/// `a` are returned. Lines whose points coincide are treated as single points.
+pub fn closest_points(
+    a: &Line3<f64>,
+    a_kind: LineKind,
+    b: &Line3<f64>,
+    b_kind: LineKind,
+) -> ClosestPoints {
+    fn clamp(x: f64, range: [f64; 2]) -> f64 {
+        x.max(range[0]).min(range[1])
+    }
+    let (ra, rb) = (a_kind.range(), b_kind.range());
+    let da = a[1] - a[0];
+    let db = b[1] - b[0];
+    let r = a[0] - b[0];
+    let aa = da.dot(&da);
+    let bb = db.dot(&db);
+    let f = db.dot(&r);
+    let (s, t) = if relative_eq!(aa, 0.0) && relative_eq!(bb, 0.0) {
+        (0.0, 0.0)
+    } else if relative_eq!(aa, 0.0) {
+        (0.0, clamp(f / bb, rb))
+    } else {
+        let c = da.dot(&r);
+        if relative_eq!(bb, 0.0) {
+            (clamp(-c / aa, ra), 0.0)
+        } else {
+            let ab = da.dot(&db);
+            let denom = aa * bb - ab * ab;
+            // parallel lines have no single closest pair, so start from a's first point
+            let s = if relative_eq!(denom, 0.0, epsilon = f64::EPSILON * aa * bb) {
+                clamp(0.0, ra)
+            } else {
+                clamp((ab * f - c * bb) / denom, ra)
+            };
+            let t = (ab * s + f) / bb;
+            if t < rb[0] {
+                (clamp((ab * rb[0] - c) / aa, ra), rb[0])
+            } else if t > rb[1] {
+                (clamp((ab * rb[1] - c) / aa, ra), rb[1])
+            } else {
+                (s, t)
+            }
+        }
+    };
+    let points = [a[0] + da * s, b[0] + db * t];
+    ClosestPoints {
+        points,
+        params: [s, t],
+        distance: na::distance(&points[0], &points[1]),
+    }
+}
+
+/// Intersects two line segments.
+///
+/// Returns the point where they cross, or `None` if they don't touch. Segments that overlap along
+/// a shared line return the overlap's point closest to the start of `a`. With the `robust` feature,
+/// segments must also be exactly coplanar according to `predicates::orient3d`.
 pub fn line_intersection(a: &Line3<f64>, b: &Line3<f64>) -> Option<Point3<f64>> {
-    let _delta_a = a[1] - a[0];
-    let _delta_b = b[1] - b[0];
-    panic!("Not Implemented: 3D Line Intersection")
+    if cfg!(feature = "robust") && super::predicates::orient3d(&a[0], &a[1], &b[0], &b[1]) != 0.0 {
+        return None;
+    }
+    let res = closest_points(a, LineKind::Segment, b, LineKind::Segment);
+    if relative_eq!(res.points[0], res.points[1], max_relative = 1.0e-12) {
+        Some(na::center(&res.points[0], &res.points[1]))
+    } else {
+        None
+    }
+}
+
+/// Finds the point of a triangle, including its interior, closest to `p`.
+pub fn closest_point_on_triangle(p: &Point3<f64>, tri: &[Point3<f64>; 3]) -> Point3<f64> {
+    let w = triangle_weights(p, tri);
+    Point3::from(tri[0].coords * w[0] + tri[1].coords * w[1] + tri[2].coords * w[2])
+}
+
+/// The barycentric weights of the point of a triangle closest to `p` (after Ericson's "Real-Time
+/// Collision Detection").
+pub(crate) fn triangle_weights(p: &Point3<f64>, tri: &[Point3<f64>; 3]) -> [f64; 3] {
+    let [a, b, c] = *tri;
+    let (ab, ac, ap) = (b - a, c - a, p - a);
+    let (d1, d2) = (ab.dot(&ap), ac.dot(&ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return [1.0, 0.0, 0.0];
+    }
+    let bp = p - b;
+    let (d3, d4) = (ab.dot(&bp), ac.dot(&bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return [0.0, 1.0, 0.0];
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        let t = d1 / (d1 - d3);
+        return [1.0 - t, t, 0.0];
+    }
+    let cp = p - c;
+    let (d5, d6) = (ab.dot(&cp), ac.dot(&cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return [0.0, 0.0, 1.0];
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        let t = d2 / (d2 - d6);
+        return [1.0 - t, 0.0, t];
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        let t = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return [0.0, 1.0 - t, t];
+    }
+    // inside the face; a triangle without area never gets here
+    let denom = 1.0 / (va + vb + vc);
+    let (v, w) = (vb * denom, vc * denom);
+    [1.0 - v - w, v, w]
+}
+
+/// Whether two triangles touch or intersect, including when they are coplanar.
+pub fn triangles_intersect(a: &[Point3<f64>; 3], b: &[Point3<f64>; 3]) -> bool {
+    let edges = |t: &[Point3<f64>; 3]| [[t[0], t[1]], [t[1], t[2]], [t[2], t[0]]];
+    let pierces = |e: &Line3<f64>, t: &[Point3<f64>; 3]| {
+        let len = na::distance(&e[0], &e[1]);
+        len > 0.0
+            && ray::Ray3::towards(e[0], &e[1])
+                .intersect_triangle(t, false)
+                .map_or(false, |hit| hit.distance <= len)
+    };
+    if edges(a).iter().any(|e| pierces(e, b)) || edges(b).iter().any(|e| pierces(e, a)) {
+        return true;
+    }
+    // what's left can only touch within the plane of one of the triangles
+    let inside =
+        |p: &Point3<f64>, t: &[Point3<f64>; 3]| relative_eq!(closest_point_on_triangle(p, t), *p);
+    edges(a)
+        .iter()
+        .any(|e| edges(b).iter().any(|f| line_intersection(e, f).is_some()))
+        || a.iter().any(|p| inside(p, b))
+        || b.iter().any(|p| inside(p, a))
+}
+
+/// Clips a parametric range `t` against one face of a Liang–Barsky bound, where `p` is the line's
+/// delta across the face and `q` its start's distance inside it.
+fn lb_clip_edge(p: f64, q: f64, mut t: [f64; 2]) -> Option<[f64; 2]> {
+    let parallel = if cfg!(feature = "robust") {
+        p == 0.0
+    } else {
+        relative_eq!(p, 0.0)
+    };
+    if parallel {
+        return if q < 0.0 { None } else { Some(t) };
+    }
+    let r = q / p;
+    if p < 0.0 {
+        if r > t[1] {
+            return None;
+        }
+        if r > t[0] {
+            t[0] = r
+        }
+    } else {
+        if r < t[0] {
+            return None;
+        }
+        if r < t[1] {
+            t[1] = r
+        }
+    }
+    Some(t)
+}
+
+/// Clips a segment to an axis-aligned box (Liang–Barsky); the 3D counterpart of `plane::lb_clip`.
+///
+/// Returns the part of `line` inside the box along with whether each end was moved, or `None` if
+/// the line misses it.
+pub fn lb_clip<B: Into<Aabb3<f64>>>(
+    line: &Line3<f64>,
+    bound: B,
+) -> Option<(Line3<f64>, [bool; 2])> {
+    let bound = bound.into();
+    let delta = line[1] - line[0];
+    let mut t = [0.0, 1.0];
+    for axis in 0..3 {
+        t = lb_clip_edge(-delta[axis], line[0][axis] - bound.min[axis], t)?;
+        t = lb_clip_edge(delta[axis], bound.max[axis] - line[0][axis], t)?;
+    }
+    let clipped = [!relative_eq!(t[0], 0.0), !relative_eq!(t[1], 1.0)];
+    let mut res = *line;
+    for i in 0..2 {
+        if clipped[i] {
+            res[i] = line[0] + delta * t[i];
+        }
+    }
+    Some((res, clipped))
+}
+
+/// Orders the two opposite corners of a box so that the first has the lowest coordinates and the
+/// second the highest.
+pub fn reorder_prism<N: RealField>(prism: &Line3<N>) -> Line3<N> {
+    let aabb = Aabb3::from(prism);
+    [aabb.min, aabb.max]
+}
+
+/// The Cohen–Sutherland outcode of a point against a box: one bit for each face the point lies
+/// beyond, from the high bit down above, below, left, right, in front (before the lowest z) and
+/// behind. Points inside give 0, and a segment whose ends share a bit misses the box.
+pub fn outcode<B: Into<Aabb3<f64>>>(p: &Point3<f64>, bound: B) -> u8 {
+    const UP: u8 = 0b100000;
+    const DOWN: u8 = 0b010000;
+    const LEFT: u8 = 0b001000;
+    const RIGHT: u8 = 0b000100;
+    const IN: u8 = 0b000010;
+    const OUT: u8 = 0b000001;
+
+    let bound = bound.into();
+    let mut res = 0;
+    if p.x < bound.min.x {
+        res |= LEFT
+    } else if p.x > bound.max.x {
+        res |= RIGHT
+    }
+
+    if p.y < bound.min.y {
+        res |= DOWN
+    } else if p.y > bound.max.y {
+        res |= UP
+    }
+
+    if p.z < bound.min.z {
+        res |= IN
+    } else if p.z > bound.max.z {
+        res |= OUT
+    }
+    res
 }
 
 pub fn ndc_to_screen(ndc: &Point3<f64>, size: &Point2<f64>, clip: &Vector2<f64>) -> Point2<f64> {
@@ -19,6 +354,15 @@ pub fn ndc_to_screen(ndc: &Point3<f64>, size: &Point2<f64>, clip: &Vector2<f64>)
     )
 }
 
+/// The inverse of `ndc_to_screen`: where a pixel lies in normalized device coordinates, from -1 at
+/// the viewport's lower edges to 1 at its upper ones.
+pub fn screen_to_ndc(pixel: &Point2<f64>, size: &Point2<f64>, clip: &Vector2<f64>) -> Point2<f64> {
+    Point2::new(
+        (pixel[0] - clip[0]) / (size[0] / 2.0) - 1.0,
+        (pixel[1] - clip[1]) / (size[1] / 2.0) - 1.0,
+    )
+}
+
 //use polyhedron::*;
 
 // #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -150,38 +494,6 @@ pub fn ndc_to_screen(ndc: &Point3<f64>, size: &Point2<f64>, clip: &Vector2<f64>)
 //             self.0[0] * r.0[1] - self.0[1] * r.0[0],
 //         ])
 //     }
-
-//     pub fn outcode<R>(self, prism: Point2<Point3<R>>) -> u8
-//     where
-//         N: PartialOrd<R>,
-//     {
-//         const UP: u8 = 0b100000;
-//         const DOWN: u8 = 0b010000;
-//         const LEFT: u8 = 0b001000;
-//         const RIGHT: u8 = 0b000100;
-//         const IN: u8 = 0b000010;
-//         const OUT: u8 = 0b000001;
-
-//         let mut res = 0x000000;
-//         if self[0] < prism[0][0] {
-//             res &= LEFT
-//         } else if self[0] > prism[1][0] {
-//             res &= RIGHT
-//         }
-
-//         if self[1] < prism[0][1] {
-//             res &= DOWN
-//         } else if self[1] > prism[1][1] {
-//             res &= UP
-//         }
-
-//         if self[2] < prism[0][2] {
-//             res &= IN
-//         } else if self[2] > prism[1][2] {
-//             res &= OUT
-//         }
-//         res
-//     }
 // }
 
 // impl Point3<f64> {
@@ -201,108 +513,211 @@ pub fn ndc_to_screen(ndc: &Point3<f64>, size: &Point2<f64>, clip: &Vector2<f64>)
 //         }
 //         None
 //     }
-
-//     pub fn lb_clip(mut self, mut bound: Self, epsilon: f64) -> Option<(Self, [bool; 2])> {
-//         fn clip(p: f64, q: f64, mut t: [f64; 2], epsilon: f64) -> Option<[f64; 2]> {
-//             println!("P: {} Q: {} T: {:?}", p, q, t);
-//             if p.abs() < epsilon && q < 0.0 {
-//                 return None;
-//             }
-//             let r = q / p;
-//             //println!("R: {}", r);
-//             if p < 0.0 {
-//                 if r > t[1] {
-//                     return None;
-//                 }
-//                 if r > t[0] {
-//                     t[0] = r
-//                 }
-//             } else {
-//                 if r < t[0] {
-//                     return None;
-//                 }
-//                 if r < t[1] {
-//                     t[1] = r
-//                 }
-//             }
-//             Some(t)
-//         }
-
-//         println!("Clipping {} with {}", self, bound);
-
-//         // making sure the line defining the boundary is positive
-//         bound = bound.reorder_prism();
-
-//         let delta = self.delta();
-
-//         let clip = clip(
-//             delta[2],
-//             bound[1][2] - self[0][2],
-//             clip(
-//                 -delta[2],
-//                 -(bound[0][2] - self[0][2]),
-//                 clip(
-//                     delta[1],
-//                     bound[1][1] - self[0][1],
-//                     clip(
-//                         -delta[1],
-//                         -(bound[0][1] - self[0][1]),
-//                         clip(
-//                             delta[0],
-//                             bound[1][0] - self[0][0],
-//                             clip(-delta[0], -(bound[0][0] - self[0][0]), [0.0, 1.0], epsilon)?,
-//                             epsilon,
-//                         )?,
-//                         epsilon,
-//                     )?,
-//                     epsilon,
-//                 )?,
-//                 epsilon,
-//             )?,
-//             epsilon,
-//         )?;
-
-//         let mut clipped = [false, false];
-//         if clip[0].abs() < epsilon {
-//             clipped[0] = true;
-//             self[0][0] = self[0][0] + clip[0] * delta[0];
-//             self[0][1] = self[0][1] + clip[0] * delta[1];
-//             self[0][2] = self[0][2] + clip[0] * delta[2];
-//         }
-//         if clip[1].abs() < epsilon {
-//             clipped[1] = true;
-//             self[1][0] = self[0][0] + clip[1] * delta[0];
-//             self[1][1] = self[0][1] + clip[1] * delta[1];
-//             self[1][2] = self[0][2] + clip[1] * delta[2];
-//         }
-
-//         println!("Clipped: {}", self);
-
-//         Some((self, clipped))
-//     }
 // }
 
-// impl<N> Point2<Point3<N>> {
-//     /// Changes order of prism values to make all deltas positive
-//     pub fn reorder_prism(mut self) -> Self
-//     where
-//         N: PartialOrd<N> + Copy,
-//     {
-//         if self[0][0] > self[1][0] {
-//             let temp = self[1][0];
-//             self[1][0] = self[0][0];
-//             self[0][0] = temp;
-//         }
-//         if self[0][1] > self[1][1] {
-//             let temp = self[1][1];
-//             self[1][1] = self[0][1];
-//             self[0][1] = temp;
-//         }
-//         if self[0][2] > self[1][2] {
-//             let temp = self[1][2];
-//             self[1][2] = self[0][2];
-//             self[0][2] = temp;
-//         }
-//         self
-//     }
-// }
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn line(a: [f64; 3], b: [f64; 3]) -> Line3<f64> {
+        [a.into(), b.into()]
+    }
+
+    #[test]
+    fn crossing() {
+        let a = line([-1.0, 1.0, 2.0], [1.0, 1.0, 2.0]);
+        let b = line([0.5, 0.0, 1.5], [0.5, 2.0, 2.5]);
+        assert_relative_eq!(
+            line_intersection(&a, &b).unwrap(),
+            Point3::new(0.5, 1.0, 2.0)
+        );
+    }
+
+    #[test]
+    fn missing() {
+        // coplanar, but b stops short of a
+        let a = line([-1.0, 0.0, 0.0], [1.0, 0.0, 0.0]);
+        let b = line([0.0, 1.0, 0.0], [0.0, 0.5, 0.0]);
+        assert!(line_intersection(&a, &b).is_none());
+        // skew
+        let b = line([0.0, -1.0, 1.0], [0.0, 1.0, 1.0]);
+        assert!(line_intersection(&a, &b).is_none());
+    }
+
+    #[test]
+    fn skew() {
+        let a = line([-1.0, 0.0, 0.0], [1.0, 0.0, 0.0]);
+        let b = line([0.5, -1.0, 2.0], [0.5, 1.0, 2.0]);
+        let res = closest_points(&a, LineKind::Segment, &b, LineKind::Segment);
+        assert_relative_eq!(res.distance, 2.0);
+        assert_relative_eq!(res.points[0], Point3::new(0.5, 0.0, 0.0));
+        assert_relative_eq!(res.points[1], Point3::new(0.5, 0.0, 2.0));
+        assert_relative_eq!(res.params[0], 0.75);
+        assert_relative_eq!(res.params[1], 0.5);
+    }
+
+    #[test]
+    fn kinds() {
+        let a = line([0.0, 0.0, 0.0], [1.0, 0.0, 0.0]);
+        let b = line([3.0, -1.0, 1.0], [3.0, 1.0, 1.0]);
+        let seg = closest_points(&a, LineKind::Segment, &b, LineKind::Segment);
+        assert_relative_eq!(seg.points[0], Point3::new(1.0, 0.0, 0.0));
+        assert_relative_eq!(seg.distance, 5.0f64.sqrt());
+        let ray = closest_points(&a, LineKind::Ray, &b, LineKind::Segment);
+        assert_relative_eq!(ray.points[0], Point3::new(3.0, 0.0, 0.0));
+        assert_relative_eq!(ray.distance, 1.0);
+        let behind = line([1.0, 0.0, 0.0], [0.0, 0.0, 0.0]);
+        let ray = closest_points(&behind, LineKind::Ray, &b, LineKind::Line);
+        assert_relative_eq!(ray.points[0], Point3::new(1.0, 0.0, 0.0));
+        let inf = closest_points(&behind, LineKind::Line, &b, LineKind::Line);
+        assert_relative_eq!(inf.params[0], -2.0);
+        assert_relative_eq!(inf.distance, 1.0);
+    }
+
+    #[test]
+    fn parallel() {
+        let a = line([0.0, 0.0, 0.0], [2.0, 0.0, 0.0]);
+        let b = line([1.0, 1.0, 0.0], [3.0, 1.0, 0.0]);
+        let res = closest_points(&a, LineKind::Segment, &b, LineKind::Segment);
+        assert_relative_eq!(res.distance, 1.0);
+        let b = line([1.0, 0.0, 0.0], [3.0, 0.0, 0.0]);
+        assert!(line_intersection(&a, &b).is_some());
+    }
+
+    #[test]
+    fn degenerate() {
+        let p = line([1.0, 1.0, 1.0], [1.0, 1.0, 1.0]);
+        let a = line([0.0, 0.0, 0.0], [2.0, 0.0, 0.0]);
+        let res = closest_points(&p, LineKind::Segment, &a, LineKind::Segment);
+        assert_relative_eq!(res.points[1], Point3::new(1.0, 0.0, 0.0));
+        assert_relative_eq!(res.distance, 2.0f64.sqrt());
+        let res = closest_points(&a, LineKind::Segment, &p, LineKind::Segment);
+        assert_relative_eq!(res.points[0], Point3::new(1.0, 0.0, 0.0));
+        assert_relative_eq!(
+            closest_points(&p, LineKind::Line, &p, LineKind::Ray).distance,
+            0.0
+        );
+    }
+
+    #[test]
+    fn triangle_closest_point() {
+        let tri = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+        ];
+        let closest = |x, y, z| closest_point_on_triangle(&Point3::new(x, y, z), &tri);
+        assert_relative_eq!(closest(0.5, 0.5, 3.0), Point3::new(0.5, 0.5, 0.0));
+        assert_relative_eq!(closest(-1.0, -1.0, 1.0), tri[0]);
+        assert_relative_eq!(closest(3.0, -1.0, 0.0), tri[1]);
+        assert_relative_eq!(closest(1.0, -1.0, 0.0), Point3::new(1.0, 0.0, 0.0));
+        assert_relative_eq!(closest(2.0, 2.0, -1.0), Point3::new(1.0, 1.0, 0.0));
+        assert_relative_eq!(closest(-1.0, 1.0, 0.0), Point3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn triangle_overlap() {
+        let a = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+        ];
+        let through = [
+            Point3::new(0.5, 0.5, -1.0),
+            Point3::new(0.5, 0.5, 1.0),
+            Point3::new(3.0, 3.0, 0.0),
+        ];
+        assert!(triangles_intersect(&a, &through));
+        let above = [
+            Point3::new(0.5, 0.5, 1.0),
+            Point3::new(1.5, 0.5, 1.0),
+            Point3::new(0.5, 1.5, 2.0),
+        ];
+        assert!(!triangles_intersect(&a, &above));
+        let coplanar = [
+            Point3::new(0.25, 0.25, 0.0),
+            Point3::new(0.5, 0.25, 0.0),
+            Point3::new(0.25, 0.5, 0.0),
+        ];
+        assert!(triangles_intersect(&a, &coplanar));
+        assert!(triangles_intersect(&coplanar, &a));
+        let apart = [
+            Point3::new(3.0, 3.0, 0.0),
+            Point3::new(4.0, 3.0, 0.0),
+            Point3::new(3.0, 4.0, 0.0),
+        ];
+        assert!(!triangles_intersect(&a, &apart));
+        let touching = [
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(3.0, 3.0, 0.0),
+            Point3::new(1.0, 1.0, 1.0),
+        ];
+        assert!(triangles_intersect(&a, &touching));
+    }
+
+    #[test]
+    #[allow(clippy::needless_borrows_for_generic_args)]
+    fn clip_segment() {
+        let bound = line([1.0, 1.0, 1.0], [-1.0, -1.0, -1.0]);
+        let (res, clipped) = lb_clip(&line([-2.0, 0.0, 0.0], [2.0, 0.5, 0.5]), &bound).unwrap();
+        assert_eq!(clipped, [true, true]);
+        assert_relative_eq!(res[0], Point3::new(-1.0, 0.125, 0.125));
+        assert_relative_eq!(res[1], Point3::new(1.0, 0.375, 0.375));
+        let (res, clipped) = lb_clip(&line([0.0, 0.0, 0.0], [0.0, 0.0, 4.0]), &bound).unwrap();
+        assert_eq!(clipped, [false, true]);
+        assert_relative_eq!(res[1], Point3::new(0.0, 0.0, 1.0));
+        let inside = line([0.5, -0.5, 0.0], [-0.5, 0.5, 0.25]);
+        assert_eq!(lb_clip(&inside, &bound).unwrap().1, [false, false]);
+        assert!(lb_clip(&line([-2.0, 2.0, 0.0], [2.0, 2.0, 0.0]), &bound).is_none());
+        assert!(lb_clip(&line([0.0, 0.0, 2.0], [3.0, 0.0, 0.5]), &bound).is_none());
+    }
+
+    #[test]
+    fn clip_segment_aabb() {
+        let bound = Aabb3::new(&Point3::new(1.0, 1.0, 1.0), &Point3::new(-1.0, -1.0, -1.0));
+        let segment = line([-2.0, 0.0, 0.0], [2.0, 0.5, 0.5]);
+        let (res, clipped) = lb_clip(&segment, bound).unwrap();
+        assert_eq!(clipped, [true, true]);
+        assert_relative_eq!(res[0], Point3::new(-1.0, 0.125, 0.125));
+        assert_relative_eq!(res[1], Point3::new(1.0, 0.375, 0.375));
+        assert!(lb_clip(&line([-2.0, 2.0, 0.0], [2.0, 2.0, 0.0]), bound).is_none());
+    }
+
+    #[test]
+    fn outcodes() {
+        let bound = line([1.0, 1.0, 1.0], [-1.0, -1.0, -1.0]);
+        assert_eq!(
+            reorder_prism(&bound),
+            line([-1.0, -1.0, -1.0], [1.0, 1.0, 1.0])
+        );
+        assert_eq!(outcode(&Point3::new(0.0, 0.5, -1.0), bound), 0);
+        assert_eq!(outcode(&Point3::new(2.0, 0.0, 0.0), bound), 0b000100);
+        assert_eq!(outcode(&Point3::new(-2.0, 3.0, -4.0), bound), 0b101010);
+        // both ends are beyond the right face, so the segment can't reach the box
+        let (a, b) = (Point3::new(2.0, -3.0, 0.0), Point3::new(3.0, 3.0, 0.0));
+        assert_ne!(outcode(&a, bound) & outcode(&b, bound), 0);
+        assert!(lb_clip(&[a, b], bound).is_none());
+    }
+
+    #[test]
+    fn plane_frame() {
+        let plane = Plane::from_points(
+            &Point3::new(0.0, 0.0, 1.0),
+            &Point3::new(1.0, 0.0, 1.0),
+            &Point3::new(0.0, 1.0, 1.0),
+        )
+        .unwrap();
+        assert_relative_eq!(plane.distance(&Point3::new(3.0, 4.0, 3.0)), 2.0);
+        let [x, y] = plane.axes();
+        assert_relative_eq!(x.into_inner(), Vector3::x());
+        assert_relative_eq!(y.into_inner(), Vector3::y());
+        let tilted = Plane::new(Point3::new(1.0, 2.0, 3.0), Vector3::new(1.0, -2.0, 0.5));
+        let [x, y] = tilted.axes();
+        assert_relative_eq!(x.cross(&y), tilted.normal.into_inner(), epsilon = 1.0e-12);
+        let p = Point3::new(-1.0, 0.5, 2.0);
+        let back = tilted.from_local(&tilted.to_local(&p));
+        assert_relative_eq!(back, tilted.project(&p), epsilon = 1.0e-12);
+        assert_relative_eq!(tilted.distance(&back), 0.0, epsilon = 1.0e-12);
+        assert!(Plane::from_points(&p, &p, &back).is_none());
+    }
+}
@@ -1,8 +1,51 @@
-use na::Matrix3;
+use super::aabb::*;
+use na::{Matrix3, Point3, Vector3};
 
 use std::iter::FromIterator;
 use std::ops::{Index, IndexMut};
 
+/// A point type whose position `Polyhedron` queries can read, and build new points from.
+pub trait Vertex: Copy {
+    fn position(&self) -> Point3<f64>;
+    fn from_position(p: Point3<f64>) -> Self;
+}
+
+impl Vertex for [f32; 3] {
+    fn position(&self) -> Point3<f64> {
+        Point3::new(f64::from(self[0]), f64::from(self[1]), f64::from(self[2]))
+    }
+    fn from_position(p: Point3<f64>) -> Self {
+        [p.x as f32, p.y as f32, p.z as f32]
+    }
+}
+
+impl Vertex for [f64; 3] {
+    fn position(&self) -> Point3<f64> {
+        Point3::from(*self)
+    }
+    fn from_position(p: Point3<f64>) -> Self {
+        [p.x, p.y, p.z]
+    }
+}
+
+impl Vertex for Point3<f32> {
+    fn position(&self) -> Point3<f64> {
+        na::convert(*self)
+    }
+    fn from_position(p: Point3<f64>) -> Self {
+        na::convert(p)
+    }
+}
+
+impl Vertex for Point3<f64> {
+    fn position(&self) -> Point3<f64> {
+        *self
+    }
+    fn from_position(p: Point3<f64>) -> Self {
+        p
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Polyhedron<P> {
     pub points: Vec<P>,
@@ -52,6 +95,125 @@ impl<P> Polyhedron<P> {
     }
 }
 
+impl<P: Vertex> Polyhedron<P> {
+    /// The positions of a face's corners.
+    pub fn face_points(&self, face: usize) -> [Point3<f64>; 3] {
+        let f = self.faces[face];
+        [
+            self.points[f[0] as usize].position(),
+            self.points[f[1] as usize].position(),
+            self.points[f[2] as usize].position(),
+        ]
+    }
+
+    /// The axis-aligned bounding box of the points, or `None` if there are none.
+    pub fn bounds(&self) -> Option<Aabb3<f64>> {
+        Aabb3::from_points(&self.points.iter().map(Vertex::position).collect::<Vec<_>>())
+    }
+}
+
+/// The mass properties of a closed mesh, treated as a solid of density 1.
+#[derive(Copy, Clone, Debug)]
+pub struct MassProperties {
+    /// Also the mass, at density 1.
+    pub volume: f64,
+    pub surface_area: f64,
+    pub center_of_mass: Point3<f64>,
+    /// The inertia tensor about `center_of_mass`. Scale it by the density for other materials.
+    pub inertia: Matrix3<f64>,
+}
+
+impl<P: Vertex> Polyhedron<P> {
+    /// Computes the exact mass properties of this mesh, by the divergence theorem (after Eberly's
+    /// "Polyhedral Mass Properties (Revisited)").
+    ///
+    /// The mesh must be closed with outward counter-clockwise faces; inside-out meshes give a
+    /// negative volume, and open ones give meaningless results. A mesh without volume has its
+    /// center of mass at its first point and no inertia.
+    pub fn mass_properties(&self) -> MassProperties {
+        fn subexpressions(w0: f64, w1: f64, w2: f64) -> [f64; 6] {
+            let t0 = w0 + w1;
+            let f1 = t0 + w2;
+            let t1 = w0 * w0;
+            let t2 = t1 + w1 * t0;
+            let f2 = t2 + w2 * f1;
+            let f3 = w0 * t1 + w1 * t2 + w2 * f2;
+            [
+                f1,
+                f2,
+                f3,
+                f2 + w0 * (f1 + w0),
+                f2 + w1 * (f1 + w1),
+                f2 + w2 * (f1 + w2),
+            ]
+        }
+        // integrate relative to a point near the mesh, to keep far-off meshes precise
+        let origin = self
+            .points
+            .first()
+            .map_or(Point3::origin(), |p| p.position());
+        // 1, x, y, z, x², y², z², xy, yz, zx
+        let mut int = [0.0; 10];
+        let mut surface_area = 0.0;
+        for face in 0..self.faces.len() {
+            let [p0, p1, p2] = self.face_points(face);
+            let (p0, p1, p2) = (p0 - origin, p1 - origin, p2 - origin);
+            let d = (p1 - p0).cross(&(p2 - p0));
+            surface_area += d.norm() / 2.0;
+            let fx = subexpressions(p0.x, p1.x, p2.x);
+            let fy = subexpressions(p0.y, p1.y, p2.y);
+            let fz = subexpressions(p0.z, p1.z, p2.z);
+            int[0] += d.x * fx[0];
+            int[1] += d.x * fx[1];
+            int[2] += d.y * fy[1];
+            int[3] += d.z * fz[1];
+            int[4] += d.x * fx[2];
+            int[5] += d.y * fy[2];
+            int[6] += d.z * fz[2];
+            int[7] += d.x * (p0.y * fx[3] + p1.y * fx[4] + p2.y * fx[5]);
+            int[8] += d.y * (p0.z * fy[3] + p1.z * fy[4] + p2.z * fy[5]);
+            int[9] += d.z * (p0.x * fz[3] + p1.x * fz[4] + p2.x * fz[5]);
+        }
+        let mult = [
+            1.0 / 6.0,
+            1.0 / 24.0,
+            1.0 / 24.0,
+            1.0 / 24.0,
+            1.0 / 60.0,
+            1.0 / 60.0,
+            1.0 / 60.0,
+            1.0 / 120.0,
+            1.0 / 120.0,
+            1.0 / 120.0,
+        ];
+        for (i, m) in int.iter_mut().zip(mult.iter()) {
+            *i *= m;
+        }
+        let volume = int[0];
+        if relative_eq!(volume, 0.0) {
+            return MassProperties {
+                volume,
+                surface_area,
+                center_of_mass: origin,
+                inertia: Matrix3::zeros(),
+            };
+        }
+        let c = Vector3::new(int[1], int[2], int[3]) / volume;
+        let xx = int[5] + int[6] - volume * (c.y * c.y + c.z * c.z);
+        let yy = int[4] + int[6] - volume * (c.z * c.z + c.x * c.x);
+        let zz = int[4] + int[5] - volume * (c.x * c.x + c.y * c.y);
+        let xy = -(int[7] - volume * c.x * c.y);
+        let yz = -(int[8] - volume * c.y * c.z);
+        let zx = -(int[9] - volume * c.z * c.x);
+        MassProperties {
+            volume,
+            surface_area,
+            center_of_mass: origin + c,
+            inertia: Matrix3::new(xx, xy, zx, xy, yy, yz, zx, yz, zz),
+        }
+    }
+}
+
 impl<P> Index<usize> for Polyhedron<P> {
     type Output = P;
     fn index(&self, i: usize) -> &P {
@@ -195,7 +357,80 @@ impl Polyhedron<[f32; 3]> {
         }
     }
 
+    /// The inertia tensor about the center of mass, for a density of 1; see `mass_properties`.
     pub fn inertia_moment(&self) -> Matrix3<f32> {
-        unimplemented!()
+        na::convert(self.mass_properties().inertia)
+    }
+}
+
+/// A unit cube centered on `(x, y, z)`, for the collision tests.
+#[cfg(test)]
+pub(crate) fn cube_at(x: f64, y: f64, z: f64) -> Polyhedron<Point3<f64>> {
+    let cube = Polyhedron::cube();
+    Polyhedron {
+        points: cube
+            .points
+            .iter()
+            .map(|p| p.position() + Vector3::new(x, y, z))
+            .collect(),
+        faces: cube.faces,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cube_mass() {
+        let props = Polyhedron::cube().mass_properties();
+        assert_relative_eq!(props.volume, 1.0);
+        assert_relative_eq!(props.surface_area, 6.0);
+        assert_relative_eq!(props.center_of_mass, Point3::origin());
+        assert_relative_eq!(props.inertia, Matrix3::identity() / 6.0);
+        assert_relative_eq!(
+            Polyhedron::cube().inertia_moment(),
+            Matrix3::identity() / 6.0
+        );
+    }
+
+    #[test]
+    fn octohedron_mass() {
+        // two square pyramids of base 1 and height 0.5, joined at their bases on the xz plane
+        let props = Polyhedron::octohedron().mass_properties();
+        assert_relative_eq!(props.volume, 1.0 / 3.0);
+        assert_relative_eq!(props.surface_area, 8.0f64.sqrt());
+        assert_relative_eq!(props.center_of_mass, Point3::origin());
+        assert_relative_eq!(
+            props.inertia,
+            Matrix3::from_diagonal(&Vector3::new(1.0 / 40.0, 1.0 / 30.0, 1.0 / 40.0))
+        );
+    }
+
+    #[test]
+    fn moved_mass() {
+        // a 2x1x1 box far from the origin, made by stretching and moving the cube
+        let mesh = Polyhedron::cube().map(|p| {
+            [
+                f64::from(p[0]) * 2.0 + 1000.0,
+                f64::from(p[1]) - 50.0,
+                f64::from(p[2]) + 3.0,
+            ]
+        });
+        let props = mesh.mass_properties();
+        assert_relative_eq!(props.volume, 2.0, epsilon = 1.0e-9);
+        assert_relative_eq!(props.surface_area, 10.0, epsilon = 1.0e-9);
+        assert_relative_eq!(
+            props.center_of_mass,
+            Point3::new(1000.0, -50.0, 3.0),
+            epsilon = 1.0e-9
+        );
+        let m = props.volume;
+        let expected = Matrix3::from_diagonal(&Vector3::new(
+            m * (1.0 + 1.0) / 12.0,
+            m * (4.0 + 1.0) / 12.0,
+            m * (4.0 + 1.0) / 12.0,
+        ));
+        assert_relative_eq!(props.inertia, expected, epsilon = 1.0e-9);
     }
 }
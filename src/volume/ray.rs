@@ -0,0 +1,174 @@
+use super::polyhedron::*;
+use na::{Point3, Unit, Vector3};
+
+#[derive(Copy, Clone, Debug)]
+pub struct Ray3 {
+    pub origin: Point3<f64>,
+    pub dir: Unit<Vector3<f64>>,
+}
+
+/// Where a ray hits a triangle.
+#[derive(Copy, Clone, Debug)]
+pub struct TriangleHit {
+    /// Distance from the ray's origin to the hit.
+    pub distance: f64,
+    /// The weights of the triangle's corners at the hit, which sum to 1.
+    pub barycentric: [f64; 3],
+    /// Whether the hit is on the side of the triangle its winding faces away from.
+    pub backface: bool,
+}
+
+/// Where a ray hits a `Polyhedron`.
+#[derive(Copy, Clone, Debug)]
+pub struct RayHit {
+    /// Index of the face that was hit, into `Polyhedron::faces`.
+    pub face: usize,
+    /// Distance from the ray's origin to the hit.
+    pub distance: f64,
+    /// The weights of the face's corners at the hit, in the order they appear in the face.
+    pub barycentric: [f64; 3],
+    /// The face's normal, from its counter-clockwise winding.
+    pub normal: Unit<Vector3<f64>>,
+}
+
+impl RayHit {
+    pub(crate) fn new(face: usize, tri: &[Point3<f64>; 3], hit: &TriangleHit) -> Self {
+        RayHit {
+            face,
+            distance: hit.distance,
+            barycentric: hit.barycentric,
+            normal: Unit::new_normalize((tri[1] - tri[0]).cross(&(tri[2] - tri[0]))),
+        }
+    }
+}
+
+impl Ray3 {
+    pub fn new(origin: Point3<f64>, dir: Vector3<f64>) -> Self {
+        Ray3 {
+            origin,
+            dir: Unit::new_normalize(dir),
+        }
+    }
+
+    /// A ray starting at `from`, pointing towards `to`.
+    pub fn towards(from: Point3<f64>, to: &Point3<f64>) -> Self {
+        Ray3::new(from, to - from)
+    }
+
+    /// The point `distance` along this ray.
+    pub fn at(&self, distance: f64) -> Point3<f64> {
+        self.origin + self.dir.as_ref() * distance
+    }
+
+    /// Intersects this ray with a triangle (Möller–Trumbore).
+    ///
+    /// Triangles are front-facing when their corners appear counter-clockwise from the ray's
+    /// origin; with `cull_backfaces` set, hits on their other side are ignored. Rays that only
+    /// graze the triangle's plane never hit.
+    pub fn intersect_triangle(
+        &self,
+        tri: &[Point3<f64>; 3],
+        cull_backfaces: bool,
+    ) -> Option<TriangleHit> {
+        let e1 = tri[1] - tri[0];
+        let e2 = tri[2] - tri[0];
+        let p = self.dir.cross(&e2);
+        let det = e1.dot(&p);
+        if relative_eq!(det, 0.0, epsilon = f64::EPSILON * e1.norm() * e2.norm())
+            || (cull_backfaces && det < 0.0)
+        {
+            return None;
+        }
+        let inv = 1.0 / det;
+        let t = self.origin - tri[0];
+        let u = t.dot(&p) * inv;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = t.cross(&e1);
+        let v = self.dir.dot(&q) * inv;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let distance = e2.dot(&q) * inv;
+        if distance < 0.0 {
+            return None;
+        }
+        Some(TriangleHit {
+            distance,
+            barycentric: [1.0 - u - v, u, v],
+            backface: det < 0.0,
+        })
+    }
+}
+
+impl<P: Vertex> Polyhedron<P> {
+    /// Finds the nearest face this ray hits, by testing every face.
+    pub fn raycast(&self, ray: &Ray3, cull_backfaces: bool) -> Option<RayHit> {
+        let mut res: Option<RayHit> = None;
+        for face in 0..self.faces.len() {
+            let tri = self.face_points(face);
+            if let Some(hit) = ray.intersect_triangle(&tri, cull_backfaces) {
+                if res.as_ref().map_or(true, |r| hit.distance < r.distance) {
+                    res = Some(RayHit::new(face, &tri, &hit));
+                }
+            }
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn triangle() {
+        let tri = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ];
+        let ray = Ray3::new(Point3::new(0.25, 0.5, 2.0), -Vector3::z());
+        let hit = ray.intersect_triangle(&tri, true).unwrap();
+        assert_relative_eq!(hit.distance, 2.0);
+        assert_relative_eq!(hit.barycentric[0], 0.25);
+        assert_relative_eq!(hit.barycentric[1], 0.25);
+        assert_relative_eq!(hit.barycentric[2], 0.5);
+        assert!(!hit.backface);
+        let back = Ray3::new(Point3::new(0.25, 0.5, -2.0), Vector3::z());
+        assert!(back.intersect_triangle(&tri, true).is_none());
+        assert!(back.intersect_triangle(&tri, false).unwrap().backface);
+        let miss = Ray3::new(Point3::new(1.0, 1.0, 2.0), -Vector3::z());
+        assert!(miss.intersect_triangle(&tri, false).is_none());
+        let behind = Ray3::new(Point3::new(0.25, 0.5, 2.0), Vector3::z());
+        assert!(behind.intersect_triangle(&tri, false).is_none());
+    }
+
+    #[test]
+    fn cube() {
+        let cube = Polyhedron::cube();
+        let ray = Ray3::towards(Point3::new(0.1, 0.2, 3.0), &Point3::new(0.1, 0.2, 0.0));
+        let hit = cube.raycast(&ray, true).unwrap();
+        assert_relative_eq!(hit.distance, 2.5, epsilon = 1.0e-6);
+        assert_relative_eq!(hit.normal.into_inner(), Vector3::z(), epsilon = 1.0e-6);
+        assert!(cube.faces[hit.face] == [4, 5, 6] || cube.faces[hit.face] == [6, 7, 4]);
+        let sum: f64 = hit.barycentric.iter().sum();
+        assert_relative_eq!(sum, 1.0);
+        let point = cube.face_points(hit.face);
+        let at = point[0].coords * hit.barycentric[0]
+            + point[1].coords * hit.barycentric[1]
+            + point[2].coords * hit.barycentric[2];
+        assert_relative_eq!(Point3::from(at), ray.at(hit.distance), epsilon = 1.0e-6);
+    }
+
+    #[test]
+    fn inside() {
+        let cube = Polyhedron::cube();
+        let ray = Ray3::new(Point3::new(0.0, 0.0, 0.0), Vector3::x());
+        assert!(cube.raycast(&ray, true).is_none());
+        let hit = cube.raycast(&ray, false).unwrap();
+        assert_relative_eq!(hit.distance, 0.5);
+        assert_relative_eq!(hit.normal.into_inner(), Vector3::x(), epsilon = 1.0e-6);
+    }
+}
@@ -0,0 +1,139 @@
+use super::polyhedron::*;
+use collision;
+use na::{Point3, Unit, Vector3};
+use std::collections::HashSet;
+
+/// How two solids overlap; see `collision::Penetration`.
+pub type Penetration = collision::Penetration<Vector3<f64>>;
+
+/// Adds `dir` to a list of unit axes unless it's zero, or parallel to one already there in either
+/// direction.
+fn push_axis(axes: &mut Vec<Unit<Vector3<f64>>>, dir: Vector3<f64>) {
+    let axis = match Unit::try_new(dir, 0.0) {
+        Some(axis) => axis,
+        None => return,
+    };
+    if !axes
+        .iter()
+        .any(|a| relative_eq!(a.dot(&axis).abs(), 1.0, epsilon = 1.0e-9))
+    {
+        axes.push(axis);
+    }
+}
+
+/// The distinct edge directions of a mesh, one per line through the origin.
+fn edges<P: Vertex>(mesh: &Polyhedron<P>) -> Vec<Unit<Vector3<f64>>> {
+    let mut seen = HashSet::new();
+    let mut res = Vec::new();
+    for face in &mesh.faces {
+        for i in 0..3 {
+            let (a, b) = (face[i], face[(i + 1) % 3]);
+            if seen.insert((a.min(b), a.max(b))) {
+                push_axis(
+                    &mut res,
+                    mesh.points[b as usize].position() - mesh.points[a as usize].position(),
+                );
+            }
+        }
+    }
+    res
+}
+
+/// Tests two convex meshes for overlap by the separating axis theorem.
+///
+/// The candidate axes are the face normals of both meshes and the cross products of every pair of
+/// their edge directions, each counted once however many faces or edges share it. Returns `None`
+/// if one of them separates the meshes, and otherwise the smallest push that would; meshes that
+/// only touch overlap with a depth of 0. Concave meshes are treated as their convex hulls would be
+/// only by chance.
+pub fn sat_collision<P: Vertex, Q: Vertex>(
+    a: &Polyhedron<P>,
+    b: &Polyhedron<Q>,
+) -> Option<Penetration> {
+    let pa = a.points.iter().map(Vertex::position).collect::<Vec<_>>();
+    let pb = b.points.iter().map(Vertex::position).collect::<Vec<_>>();
+    if pa.is_empty() || pb.is_empty() {
+        return None;
+    }
+    let normal = |t: [Point3<f64>; 3]| (t[1] - t[0]).cross(&(t[2] - t[0]));
+    let mut axes = Vec::new();
+    for f in 0..a.faces.len() {
+        push_axis(&mut axes, normal(a.face_points(f)));
+    }
+    for f in 0..b.faces.len() {
+        push_axis(&mut axes, normal(b.face_points(f)));
+    }
+    let eb = edges(b);
+    for ea in edges(a) {
+        for e in &eb {
+            let axis = ea.cross(e);
+            // parallel edges give no new axis, and a face normal already covers them
+            if axis.norm() > 1.0e-9 {
+                push_axis(&mut axes, axis);
+            }
+        }
+    }
+    collision::separating_axes(axes, &pa, &pb)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use na::UnitQuaternion;
+
+    #[test]
+    fn cubes() {
+        let a = cube_at(0.0, 0.0, 0.0);
+        let pen = sat_collision(&a, &cube_at(0.75, 0.1, 0.0)).unwrap();
+        assert_relative_eq!(pen.depth, 0.25, epsilon = 1.0e-9);
+        assert_relative_eq!(pen.axis.into_inner(), Vector3::x(), epsilon = 1.0e-9);
+        let pen = sat_collision(&a, &cube_at(0.0, -0.9, 0.0)).unwrap();
+        assert_relative_eq!(pen.axis.into_inner(), -Vector3::y(), epsilon = 1.0e-9);
+        assert!(sat_collision(&a, &cube_at(0.0, 0.0, 1.5)).is_none());
+    }
+
+    #[test]
+    fn contained() {
+        let big = Polyhedron {
+            points: Polyhedron::cube()
+                .points
+                .iter()
+                .map(|p| Point3::from(p.position().coords * 4.0))
+                .collect(),
+            faces: Polyhedron::cube().faces,
+        };
+        let pen = sat_collision(&big, &cube_at(0.0, 0.0, 1.0)).unwrap();
+        assert_relative_eq!(pen.depth, 1.5, epsilon = 1.0e-9);
+        assert_relative_eq!(pen.axis.into_inner(), Vector3::z(), epsilon = 1.0e-9);
+    }
+
+    #[test]
+    fn distinct_edges() {
+        // 18 edges, but opposite and parallel ones share a direction: the 3 axes and a diagonal
+        // for each face
+        assert_eq!(edges(&Polyhedron::cube()).len(), 9);
+    }
+
+    #[test]
+    fn crossed_edges() {
+        // two cubes standing on edge, with those edges crossing at right angles
+        let turned = |axis: Unit<Vector3<f64>>, h: f64| {
+            let rot = UnitQuaternion::from_axis_angle(&axis, std::f64::consts::FRAC_PI_4);
+            Polyhedron {
+                points: Polyhedron::cube()
+                    .points
+                    .iter()
+                    .map(|p| rot * p.position() + Vector3::new(0.0, 0.0, h))
+                    .collect(),
+                faces: Polyhedron::cube().faces,
+            }
+        };
+        let half = 0.5f64.sqrt();
+        let a = turned(Vector3::x_axis(), 0.0);
+        // no face separates these, only the cross product of the two edges
+        assert!(sat_collision(&a, &turned(Vector3::y_axis(), 2.0 * half + 0.1)).is_none());
+        let pen = sat_collision(&a, &turned(Vector3::y_axis(), 2.0 * half - 0.1)).unwrap();
+        assert_relative_eq!(pen.depth, 0.1, epsilon = 1.0e-6);
+        assert_relative_eq!(pen.axis.into_inner(), Vector3::z(), epsilon = 1.0e-6);
+    }
+}
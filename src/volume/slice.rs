@@ -0,0 +1,584 @@
+use super::super::plane::multipolygon::*;
+use super::super::plane::polygon::*;
+use super::polyhedron::*;
+use super::Plane;
+use na::Point3;
+use std::collections::{HashMap, HashSet};
+
+/// An edge of a mesh, by its vertex indices in ascending order. Where a cut passes through a
+/// vertex rather than across an edge, the vertex is given as both ends.
+pub(crate) type EdgeKey = (u16, u16);
+
+/// How far from a plane, relative to the furthest vertex of the mesh, a vertex counts as on it.
+const ON_PLANE: f64 = 1.0e-9;
+
+/// Where an edge crossing a plane meets it: at the edge's end if that is on the plane, and
+/// otherwise somewhere along it.
+fn meeting(on: &[bool], (a, b): EdgeKey) -> EdgeKey {
+    if on[a as usize] {
+        (a, a)
+    } else if on[b as usize] {
+        (b, b)
+    } else {
+        (a, b)
+    }
+}
+
+/// A run of crossed edges, in the order the cut passes through them.
+pub(crate) struct Chain {
+    pub edges: Vec<EdgeKey>,
+    /// Whether the last edge leads back to the first, as it always does for closed meshes.
+    pub closed: bool,
+}
+
+/// Where the faces of a mesh cross a plane.
+pub(crate) struct Section {
+    /// Which side of the plane each vertex is on. Vertices on the plane count as in front if the
+    /// section was made with `on_front`, and as behind otherwise.
+    pub front: Vec<bool>,
+    /// Which vertices are on the plane, to within rounding.
+    pub on: Vec<bool>,
+    /// Every chain runs counter-clockwise around the solid as seen from the plane's positive side.
+    pub chains: Vec<Chain>,
+    /// Where each crossed edge meets the plane, by its key from `meeting`.
+    pub points: HashMap<EdgeKey, Point3<f64>>,
+}
+
+impl Section {
+    pub fn new<P: Vertex>(mesh: &Polyhedron<P>, plane: &Plane, on_front: bool) -> Self {
+        let positions = mesh.points.iter().map(Vertex::position).collect::<Vec<_>>();
+        let distances = positions
+            .iter()
+            .map(|p| plane.distance(p))
+            .collect::<Vec<_>>();
+        let eps = ON_PLANE * distances.iter().fold(0.0, |m: f64, d| m.max(d.abs()));
+        let on = distances.iter().map(|d| d.abs() <= eps).collect::<Vec<_>>();
+        let front = distances
+            .iter()
+            .zip(&on)
+            .map(|(&d, &on)| if on { on_front } else { d > 0.0 })
+            .collect::<Vec<_>>();
+        let node = |key: EdgeKey| meeting(&on, key);
+        let mut points = HashMap::new();
+        let mut next = HashMap::new();
+        let mut starts = Vec::new();
+        for face in &mesh.faces {
+            let (mut from, mut to) = (None, None);
+            for i in 0..3 {
+                let (a, b) = (face[i], face[(i + 1) % 3]);
+                if front[a as usize] == front[b as usize] {
+                    continue;
+                }
+                let key = (a.min(b), a.max(b));
+                points.entry(node(key)).or_insert_with(|| {
+                    let (p, q) = (key.0 as usize, key.1 as usize);
+                    if on[p] {
+                        positions[p]
+                    } else if on[q] {
+                        positions[q]
+                    } else {
+                        let t = distances[p] / (distances[p] - distances[q]);
+                        positions[p] + (positions[q] - positions[p]) * t
+                    }
+                });
+                // with outward faces, the cut leaves a face where its boundary goes back to front
+                if front[a as usize] {
+                    from = Some(key);
+                } else {
+                    to = Some(key);
+                }
+            }
+            if let (Some(from), Some(to)) = (from, to) {
+                next.insert(from, to);
+                starts.push(from);
+            }
+        }
+        let ends = next.values().cloned().collect::<HashSet<_>>();
+        let mut visited = HashSet::new();
+        let mut chains = Vec::new();
+        // walk open chains from their real starts first, so that none get split in two
+        let open = starts.iter().filter(|k| !ends.contains(*k));
+        for &start in open.chain(starts.iter()) {
+            if !visited.insert(start) {
+                continue;
+            }
+            let mut edges = vec![node(start)];
+            let mut cur = start;
+            let mut closed = false;
+            while let Some(&n) = next.get(&cur) {
+                if !visited.insert(n) {
+                    closed = n == start;
+                    break;
+                }
+                // several edges from one vertex on the plane all meet it there
+                if edges.last() != Some(&node(n)) {
+                    edges.push(node(n));
+                }
+                cur = n;
+            }
+            if closed && edges.len() > 1 && edges.first() == edges.last() {
+                edges.pop();
+            }
+            chains.push(Chain { edges, closed });
+        }
+        Section {
+            front,
+            on,
+            chains,
+            points,
+        }
+    }
+}
+
+impl<P: Vertex> Polyhedron<P> {
+    /// Slices this mesh with a plane, returning the outline of the cut in the plane's 2D frame
+    /// (see `Plane::to_local`).
+    ///
+    /// For a closed mesh with outward, counter-clockwise faces, the outlines of solid regions wind
+    /// counter-clockwise and those of holes clockwise, so `MultiPolygon::from_rings` turns the
+    /// result into a floor plan. Vertices on the plane count as in front of it, so faces lying in
+    /// the plane are only outlined if the mesh continues behind them. Open meshes can leave chains
+    /// that don't close; these are closed by joining their ends, and outlines without area are
+    /// dropped.
+    pub fn cross_section(&self, plane: &Plane) -> Vec<Polygon<f64>> {
+        let section = Section::new(self, plane, true);
+        section
+            .chains
+            .iter()
+            .filter_map(|chain| {
+                Polygon {
+                    points: chain
+                        .edges
+                        .iter()
+                        .map(|k| plane.to_local(&section.points[k]))
+                        .collect(),
+                }
+                .non_degenerate()
+            })
+            .collect()
+    }
+
+    /// Cuts this mesh in two along a plane, returning the parts in front of and behind it.
+    ///
+    /// Faces crossing the plane are split along it. Vertices on the plane (to within rounding)
+    /// are shared by both parts, and edges from them end there rather than gaining a crossing of
+    /// their own, so a part only gets what lies strictly on its side; faces lying in the plane
+    /// belong to neither, and come back as the cap of whichever part they closed. With `cap` set,
+    /// each part gets the cross-section triangulated over its cut, sharing vertices with the split
+    /// faces so that a closed mesh cuts into closed parts. Parts with nothing in them come back
+    /// empty.
+    ///
+    /// # Panics
+    /// If either part ends up with more vertices than a `u16` can index.
+    pub fn split(&self, plane: &Plane, cap: bool) -> [Polyhedron<P>; 2] {
+        // each part counts the vertices on the plane as on the other side, so keeps only its own
+        let sections = [
+            Section::new(self, plane, false),
+            Section::new(self, plane, true),
+        ];
+        let mut res = [
+            Part::new(self, &sections[0], true),
+            Part::new(self, &sections[1], false),
+        ];
+        for face in &self.faces {
+            for part in &mut res {
+                part.add_face(face);
+            }
+        }
+        if cap {
+            for part in &mut res {
+                for (loops, tris) in part.section.caps(plane) {
+                    for tri in &tris {
+                        let mut ids = [
+                            part.crossing(&loops[tri[0] as usize]),
+                            part.crossing(&loops[tri[1] as usize]),
+                            part.crossing(&loops[tri[2] as usize]),
+                        ];
+                        // the cap faces out of each part, away from the other
+                        if part.front {
+                            ids.reverse();
+                        }
+                        part.push_face(ids);
+                    }
+                }
+            }
+        }
+        let [front, back] = res;
+        [front.mesh, back.mesh]
+    }
+}
+
+impl Section {
+    /// Triangulates the closed chains, grouping each hole with the smallest solid around it. Gives
+    /// the edges of each group's rings in order, and counter-clockwise triangles indexing them.
+    fn caps(&self, plane: &Plane) -> Vec<(Vec<EdgeKey>, Vec<[u16; 3]>)> {
+        let rings = self
+            .chains
+            .iter()
+            .filter(|c| c.closed)
+            .map(|c| {
+                let ring = Polygon {
+                    points: c
+                        .edges
+                        .iter()
+                        .map(|k| plane.to_local(&self.points[k]))
+                        .collect(),
+                };
+                (c.edges.clone(), ring)
+            })
+            .filter(|(_, ring)| !relative_eq!(ring.signed_area(), 0.0))
+            .collect::<Vec<_>>();
+        let (mut outers, holes) = rings
+            .into_iter()
+            .partition::<Vec<_>, _>(|(_, ring)| ring.signed_area() > 0.0);
+        outers.sort_by(|l, r| l.1.signed_area().partial_cmp(&r.1.signed_area()).unwrap());
+        let mut groups = outers
+            .into_iter()
+            .map(|(edges, ring)| (edges, PolygonWithHoles::from(ring)))
+            .collect::<Vec<_>>();
+        for (edges, hole) in holes {
+            let parent = groups.iter_mut().find(|(_, p)| {
+                hole.points
+                    .iter()
+                    .any(|v| p.outer.locate(v, FillRule::NonZero) == Containment::Inside)
+            });
+            if let Some((parent_edges, parent)) = parent {
+                parent_edges.extend(edges);
+                parent.holes.push(hole);
+            }
+        }
+        groups
+            .into_iter()
+            .map(|(edges, poly)| (edges, poly.triangulate()))
+            .collect()
+    }
+}
+
+/// One side of a split, collecting the vertices it uses as it goes.
+struct Part<'a, P: 'a> {
+    source: &'a Polyhedron<P>,
+    section: &'a Section,
+    front: bool,
+    mesh: Polyhedron<P>,
+    vertices: HashMap<u16, u16>,
+    crossings: HashMap<EdgeKey, u16>,
+}
+
+impl<'a, P: Vertex> Part<'a, P> {
+    fn new(source: &'a Polyhedron<P>, section: &'a Section, front: bool) -> Self {
+        Part {
+            source,
+            section,
+            front,
+            mesh: Polyhedron {
+                points: Vec::new(),
+                faces: Vec::new(),
+            },
+            vertices: HashMap::new(),
+            crossings: HashMap::new(),
+        }
+    }
+
+    fn push(&mut self, p: P) -> u16 {
+        assert!(
+            self.mesh.points.len() <= u16::MAX as usize,
+            "Too many vertices after splitting"
+        );
+        self.mesh.points.push(p);
+        (self.mesh.points.len() - 1) as u16
+    }
+
+    fn vertex(&mut self, i: u16) -> u16 {
+        if let Some(&id) = self.vertices.get(&i) {
+            return id;
+        }
+        let id = self.push(self.source.points[i as usize]);
+        self.vertices.insert(i, id);
+        id
+    }
+
+    fn crossing(&mut self, key: &EdgeKey) -> u16 {
+        if key.0 == key.1 {
+            return self.vertex(key.0);
+        }
+        if let Some(&id) = self.crossings.get(key) {
+            return id;
+        }
+        let id = self.push(P::from_position(self.section.points[key]));
+        self.crossings.insert(*key, id);
+        id
+    }
+
+    /// Adds a triangle unless it has no area, as happens where the cut runs along one of its edges.
+    fn push_face(&mut self, ids: [u16; 3]) {
+        let [a, b, c] = [
+            self.mesh.points[ids[0] as usize].position(),
+            self.mesh.points[ids[1] as usize].position(),
+            self.mesh.points[ids[2] as usize].position(),
+        ];
+        let (ab, ac) = (b - a, c - a);
+        if !relative_eq!(
+            ab.cross(&ac).norm(),
+            0.0,
+            epsilon = f64::EPSILON * ab.norm() * ac.norm()
+        ) {
+            self.mesh.faces.push(ids);
+        }
+    }
+
+    /// Adds the part of a face on this side, as one triangle or two.
+    fn add_face(&mut self, face: &[u16; 3]) {
+        let (section, front) = (self.section, self.front);
+        let inside = |i: u16| section.front[i as usize] == front;
+        if !face.iter().any(|&i| inside(i)) {
+            return;
+        }
+        let mut poly = Vec::with_capacity(4);
+        for i in 0..3 {
+            let (a, b) = (face[i], face[(i + 1) % 3]);
+            let (ia, ib) = (inside(a), inside(b));
+            if ia {
+                poly.push(self.vertex(a));
+            }
+            if ia != ib {
+                let id = self.crossing(&meeting(&self.section.on, (a.min(b), a.max(b))));
+                // a vertex on the plane both ends the face's run on this side and is the crossing
+                if poly.last() != Some(&id) && poly.first() != Some(&id) {
+                    poly.push(id);
+                }
+            }
+        }
+        for i in 2..poly.len() {
+            self.push_face([poly[0], poly[i - 1], poly[i]]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use na::Vector3;
+
+    /// Extrudes a polygon with holes from z = 0 to z = 1 into a closed mesh.
+    fn extrude(poly: &PolygonWithHoles<f64>) -> Polyhedron<Point3<f64>> {
+        let mut poly = poly.clone();
+        poly.outer.set_winding(Winding::CounterClockwise);
+        for hole in &mut poly.holes {
+            hole.set_winding(Winding::Clockwise);
+        }
+        let flat = poly.to_polyhedron();
+        let n = flat.points.len() as u16;
+        let mut mesh = Polyhedron {
+            points: flat
+                .points
+                .iter()
+                .map(|p| Point3::new(p.x, p.y, 0.0))
+                .chain(flat.points.iter().map(|p| Point3::new(p.x, p.y, 1.0)))
+                .collect(),
+            faces: Vec::new(),
+        };
+        for f in &flat.faces {
+            mesh.faces.push([f[2], f[1], f[0]]);
+            mesh.faces.push([f[0] + n, f[1] + n, f[2] + n]);
+        }
+        let mut offset = 0;
+        for ring in poly.rings() {
+            let len = ring.points.len() as u16;
+            for i in 0..len {
+                let (a, b) = (offset + i, offset + (i + 1) % len);
+                mesh.faces.push([a, b, b + n]);
+                mesh.faces.push([b + n, a + n, a]);
+            }
+            offset += len;
+        }
+        mesh
+    }
+
+    #[test]
+    fn cube() {
+        let cube = Polyhedron::cube();
+        let res = cube.cross_section(&Plane::new(Point3::origin(), Vector3::z()));
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].winding(), Some(Winding::CounterClockwise));
+        assert_relative_eq!(res[0].area(), 1.0, epsilon = 1.0e-6);
+        let diagonal =
+            cube.cross_section(&Plane::new(Point3::origin(), Vector3::new(1.0, 1.0, 1.0)));
+        assert_eq!(diagonal.len(), 1);
+        // a regular hexagon through the middle of the unit cube
+        assert_relative_eq!(
+            diagonal[0].area(),
+            3.0f64.sqrt() * 3.0 / 4.0,
+            epsilon = 1.0e-6
+        );
+        assert!(cube
+            .cross_section(&Plane::new(Point3::new(0.0, 0.0, 2.0), Vector3::z()))
+            .is_empty());
+    }
+
+    #[test]
+    fn on_face() {
+        // faces in the plane count as in front of it, so only the sides behind are cut
+        let cube = Polyhedron::cube();
+        let top = cube.cross_section(&Plane::new(Point3::new(0.0, 0.0, 0.5), Vector3::z()));
+        assert_eq!(top.len(), 1);
+        assert_relative_eq!(top[0].area(), 1.0);
+        let bottom = Plane::new(Point3::new(0.0, 0.0, -0.5), Vector3::z());
+        assert!(cube.cross_section(&bottom).is_empty());
+    }
+
+    #[test]
+    fn floor_plan() {
+        let arena = PolygonWithHoles {
+            outer: Polygon::square(4.0, 0.0, 0.0),
+            holes: vec![
+                Polygon::square(1.0, -2.0, 0.0),
+                Polygon::square(1.0, 2.0, 0.0),
+            ],
+        };
+        let mesh = extrude(&arena);
+        let res = mesh.cross_section(&Plane::new(Point3::new(0.0, 0.0, 0.5), Vector3::z()));
+        assert_eq!(res.len(), 3);
+        let plan = MultiPolygon::from_rings(res);
+        assert_eq!(plan.polygons.len(), 1);
+        assert_eq!(plan.polygons[0].holes.len(), 2);
+        assert_relative_eq!(plan.area(), arena.area(), epsilon = 1.0e-9);
+        // slicing from below flips the frame, but solids still come out counter-clockwise
+        let below = mesh.cross_section(&Plane::new(Point3::new(0.0, 0.0, 0.5), -Vector3::z()));
+        assert_relative_eq!(
+            MultiPolygon::from_rings(below).area(),
+            arena.area(),
+            epsilon = 1.0e-9
+        );
+    }
+
+    #[test]
+    fn open_mesh() {
+        let quad = Polyhedron::quad();
+        let plane = Plane::new(Point3::origin(), Vector3::x());
+        // a flat quad only cuts a line, which has no area
+        assert!(quad.cross_section(&plane).is_empty());
+        let section = Section::new(&quad, &plane, true);
+        assert_eq!(section.chains.len(), 1);
+        assert_eq!(section.chains[0].edges.len(), 3);
+        assert!(!section.chains[0].closed);
+    }
+
+    /// Whether every edge of a mesh is shared by exactly one other face, running the other way.
+    fn closed<P>(mesh: &Polyhedron<P>) -> bool {
+        let mut edges = HashMap::new();
+        for f in &mesh.faces {
+            for i in 0..3 {
+                *edges.entry((f[i], f[(i + 1) % 3])).or_insert(0) += 1;
+            }
+        }
+        edges
+            .iter()
+            .all(|(&(a, b), &n)| n == 1 && edges.get(&(b, a)) == Some(&1))
+    }
+
+    fn volume<P: Vertex>(mesh: &Polyhedron<P>) -> f64 {
+        (0..mesh.faces.len())
+            .map(|f| {
+                let [a, b, c] = mesh.face_points(f);
+                a.coords.dot(&b.coords.cross(&c.coords)) / 6.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn split_cube() {
+        let cube = Polyhedron::cube();
+        let plane = Plane::new(Point3::new(0.0, 0.0, 0.25), Vector3::new(0.3, 0.2, 1.0));
+        let [front, back] = cube.split(&plane, true);
+        assert!(closed(&front) && closed(&back));
+        assert_relative_eq!(volume(&front) + volume(&back), 1.0, epsilon = 1.0e-6);
+        assert!(front
+            .points
+            .iter()
+            .all(|p| plane.distance(&p.position()) > -1.0e-6));
+        assert!(back
+            .points
+            .iter()
+            .all(|p| plane.distance(&p.position()) < 1.0e-6));
+        let [front, back] = cube.split(&plane, false);
+        assert!(!closed(&front) && !closed(&back));
+    }
+
+    #[test]
+    fn split_miss() {
+        let cube = Polyhedron::cube();
+        let [front, back] = cube.split(&Plane::new(Point3::new(0.0, 0.0, 2.0), Vector3::z()), true);
+        assert!(front.faces.is_empty() && front.points.is_empty());
+        assert_eq!(back.faces.len(), 12);
+        assert!(closed(&back));
+    }
+
+    /// Whether every face of a mesh has some area.
+    fn no_slivers<P: Vertex>(mesh: &Polyhedron<P>) -> bool {
+        (0..mesh.faces.len()).all(|f| {
+            let [a, b, c] = mesh.face_points(f);
+            (b - a).cross(&(c - a)).norm() > 1.0e-9
+        })
+    }
+
+    #[test]
+    fn split_on_face() {
+        let cube = Polyhedron::cube();
+        // the whole cube is behind its own top face, and nothing is in front of it
+        let top = Plane::new(Point3::new(0.0, 0.0, 0.5), Vector3::z());
+        let [front, back] = cube.split(&top, true);
+        assert!(front.faces.is_empty() && front.points.is_empty());
+        assert_relative_eq!(volume(&front), 0.0);
+        assert_eq!(back.points.len(), 8);
+        assert!(closed(&back) && no_slivers(&back));
+        assert_relative_eq!(volume(&back), 1.0, epsilon = 1.0e-9);
+        let bottom = Plane::new(Point3::new(0.0, 0.0, -0.5), Vector3::z());
+        let [front, back] = cube.split(&bottom, true);
+        assert!(back.faces.is_empty() && back.points.is_empty());
+        assert_eq!(front.points.len(), 8);
+        assert!(closed(&front) && no_slivers(&front));
+        assert_relative_eq!(volume(&front), 1.0, epsilon = 1.0e-9);
+    }
+
+    #[test]
+    fn split_through_vertices() {
+        // through two opposite vertical edges, so four vertices lie on the plane
+        let cube = Polyhedron::cube();
+        let plane = Plane::new(Point3::origin(), Vector3::new(1.0, -1.0, 0.0));
+        let [front, back] = cube.split(&plane, true);
+        for part in &[&front, &back] {
+            assert!(closed(part) && no_slivers(part));
+            assert_relative_eq!(volume(part), 0.5, epsilon = 1.0e-9);
+            // no point is repeated
+            let points = part.points.iter().map(|p| p.position()).collect::<Vec<_>>();
+            for (i, p) in points.iter().enumerate() {
+                assert!(points[i + 1..].iter().all(|q| !relative_eq!(p, q)));
+            }
+        }
+        assert!(front
+            .points
+            .iter()
+            .all(|p| plane.distance(&p.position()) > -1.0e-9));
+        assert!(back
+            .points
+            .iter()
+            .all(|p| plane.distance(&p.position()) < 1.0e-9));
+    }
+
+    #[test]
+    fn split_with_holes() {
+        let arena = PolygonWithHoles {
+            outer: Polygon::square(4.0, 0.0, 0.0),
+            holes: vec![
+                Polygon::square(1.0, -2.0, 0.0),
+                Polygon::square(1.0, 2.0, 0.0),
+            ],
+        };
+        let mesh = extrude(&arena);
+        let [top, bottom] =
+            mesh.split(&Plane::new(Point3::new(0.0, 0.0, 0.25), Vector3::z()), true);
+        assert!(closed(&top) && closed(&bottom));
+        assert_relative_eq!(volume(&top), arena.area() * 0.75, epsilon = 1.0e-9);
+        assert_relative_eq!(volume(&bottom), arena.area() * 0.25, epsilon = 1.0e-9);
+    }
+}